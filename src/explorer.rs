@@ -9,7 +9,7 @@ use crate::parser::{
 };
 
 
-fn select_element<'a>(html: &'a Element, indices: &[usize]) -> &'a Element
+fn select_element<'a, 'b>(html: &'a Element<'b>, indices: &[usize]) -> &'a Element<'b>
 {
     let mut element = html;
 
@@ -46,14 +46,16 @@ fn print_info(element: &Element)
 
                 format!("<{name}>")
             },
-            Child::Text(text) => text.clone()
+            Child::Text(text) => text.content().to_owned(),
+            Child::Comment(comment) => comment.to_string(),
+            Child::ProcessingInstruction(instruction) => instruction.to_string()
         };
 
         println!("{:4}{content}", format!("{index}:"));
     }
 }
 
-pub fn explore(html: Element)
+pub fn explore(html: &Element)
 {
     let stdin = io::stdin();
 
@@ -67,7 +69,7 @@ pub fn explore(html: Element)
 
     loop
     {
-        let this_element = select_element(&html, selector_indices.as_slices().0);
+        let this_element = select_element(html, selector_indices.as_slices().0);
 
         print_info(this_element);
 
@@ -101,17 +103,14 @@ pub fn explore(html: Element)
             {
                 Some(tag_name) =>
                 {
-                    let maybe_tag = this_element.tags().iter().find(|tag|
-                    {
-                        tag.name() == tag_name
-                    });
+                    let maybe_tag = this_element.attributes().get(tag_name);
 
                     match maybe_tag
                     {
                         Some(tag) =>
                         {
                             let name = tag.name();
-                            let content = tag.content().clone().unwrap_or_else(|| "none".to_owned());
+                            let content = tag.content().unwrap_or("none");
 
                             println!("{name} = \"{content}\"");
                         },
@@ -141,7 +140,8 @@ pub fn explore(html: Element)
                 continue;
             }
 
-            if this_element.children()[index].text().is_some()
+            let child = &this_element.children()[index];
+            if child.text().is_some() || child.is_comment()
             {
                 continue;
             }
@@ -0,0 +1,58 @@
+#![allow(clippy::suspicious_else_formatting)]
+
+pub use parser::{
+    Parser,
+    Document,
+    Element,
+    Child,
+    Comment,
+    Attributes,
+    Namespace,
+    XmlDeclaration,
+    ProcessingInstruction,
+    Dom,
+    Node,
+    NodeId,
+    NodeKind,
+    Tag,
+    Text,
+    Quote,
+    ElementBody,
+    ElementEnd,
+    Event,
+    Events,
+    Handler,
+    Serializer,
+    Serialize,
+    Formatter,
+    Minifier,
+    Selector,
+    XPath,
+    XPathNode,
+    XPathValue,
+    Doctype,
+    QuirksMode,
+    Encoding,
+    EncodingSource,
+    Position,
+    Span,
+    ParseError,
+    ParseErrorKind,
+    MinifyError,
+    MinifyErrorKind,
+    MutationError,
+    MutationErrorKind,
+    SelectorError,
+    SelectorErrorKind,
+    XPathError,
+    XPathErrorKind,
+    XmlError,
+    XmlErrorKind,
+    Diagnostic,
+    DiagnosticKind
+};
+
+pub mod parser;
+
+#[cfg(feature = "explorer")]
+pub mod explorer;
@@ -1,17 +1,21 @@
+#![allow(clippy::suspicious_else_formatting)]
+
 use std::{
     fs,
     env,
+    str,
     process
 };
 
-use parser::{
+use htmlparser::{
     Element,
+    Encoding,
+    Formatter,
     Parser
 };
 
-mod parser;
-mod explorer;
 
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
 
 fn complain(message: &str) -> !
 {
@@ -26,28 +30,88 @@ fn main()
     let filepath = args.next()
         .unwrap_or_else(|| complain("pls provide a path as argument"));
 
-    let use_explore = args.next().map(|mode|
-    {
-        mode.to_lowercase() == "explore"
-    }).unwrap_or(false);
+    let mode = args.next().map(|mode| mode.to_lowercase());
 
-    let data = fs::read_to_string(filepath)
+    let data = fs::read(&filepath)
         .unwrap_or_else(|err| complain(&format!("error reading file: {err:?}")));
 
-    let parser = Parser::new(data.chars());
+    if mode.as_deref() == Some("format")
+    {
+        format_main(&filepath, &data, args.next().as_deref());
+
+        return;
+    }
+
+    let parser = Parser::from_bytes(&data);
 
-    let html = parser.parse();
+    let document = parser.parse()
+        .unwrap_or_else(|err| complain(&format!("error parsing file: {err}")));
 
-    if use_explore
+    let html = document.root()
+        .unwrap_or_else(|| complain("no root element found"));
+
+    if mode.as_deref() == Some("explore")
     {
-        explorer::explore(html);
+        explore(html);
     } else
     {
         normal_main(html);
     }
 }
 
-fn normal_main(html: Element)
+// prints the formatted file, or with in-place overwrites it, or with check only says if its formatted
+fn format_main(filepath: &str, data: &[u8], option: Option<&str>)
+{
+    let (encoding, _) = Encoding::detect(data, None);
+    let text = encoding.decode(data);
+
+    let formatted = Formatter::new().format(&text)
+        .unwrap_or_else(|err| complain(&format!("error parsing file: {err}")));
+
+    match option.map(|option| option.to_lowercase()).as_deref()
+    {
+        None => print!("{formatted}"),
+        Some("in-place") =>
+        {
+            // theres no encoder, writing anything else back as utf8 would mangle the file
+            let (body, bom) = match data.strip_prefix(UTF8_BOM)
+            {
+                Some(body) => (body, UTF8_BOM),
+                None => (data, &[][..])
+            };
+
+            if encoding != Encoding::Utf8 || str::from_utf8(body).is_err()
+            {
+                complain(&format!("{filepath} isnt utf8 ({}), cant format it in place", encoding.name()));
+            }
+
+            fs::write(filepath, [bom, formatted.as_bytes()].concat())
+                .unwrap_or_else(|err| complain(&format!("error writing file: {err:?}")));
+        },
+        Some("check") =>
+        {
+            if formatted != text
+            {
+                complain(&format!("{filepath} is not formatted"));
+            }
+        },
+        Some(option) => complain(&format!("unknown format option {option}, expected in-place or check"))
+    }
+}
+
+#[cfg(feature = "explorer")]
+fn explore(html: &Element)
+{
+    htmlparser::explorer::explore(html);
+}
+
+#[cfg(not(feature = "explorer"))]
+fn explore(_html: &Element)
+{
+    complain("built without the explorer feature");
+}
+
+fn normal_main(html: &Element)
 {
     // nobody will know wut i wrote this for heheheheh
 
@@ -55,13 +119,11 @@ fn normal_main(html: Element)
     {
         let element = child.element()?;
 
-        let element = (element.name() == "button").then(|| element)?;
+        let element = (element.name() == "button").then_some(element)?;
 
         let img = element.children()[0].element()?.children()[0].element()?;
 
-        let tag = img.tags().iter().find(|tag| tag.name() == "src")?;
-
-        let content = tag.content();
+        let content = img.attr("src")?;
 
         let url = content.chars().skip(2).collect::<Vec<_>>();
 
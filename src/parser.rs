@@ -1,63 +1,196 @@
 use std::{
-    iter::Peekable,
+    io::{
+        self,
+        Read
+    },
+    borrow::Cow,
     ops::Index
 };
 
 use syntaxer::{
-    Tag,
     Syntaxer,
     Leaf,
     TextIterInner
 };
 
-mod syntaxer;
+use tree_builder::{
+    TreeBuilder,
+    is_childless
+};
+
+use xml::XmlParser;
+
+pub use error::{
+    ParseError,
+    ParseErrorKind,
+    MinifyError,
+    MinifyErrorKind,
+    MutationError,
+    MutationErrorKind,
+    SelectorError,
+    SelectorErrorKind,
+    XPathError,
+    XPathErrorKind,
+    XmlError,
+    XmlErrorKind,
+    Diagnostic,
+    DiagnosticKind
+};
+
+pub use attributes::Attributes;
+
+pub use dom::{
+    Dom,
+    Node,
+    NodeId,
+    NodeKind,
+    Siblings,
+    Ancestors,
+    Descendants
+};
+
+pub use doctype::{
+    Doctype,
+    QuirksMode
+};
+
+pub use encoding::{
+    Encoding,
+    EncodingSource
+};
+
+pub use foreign::Namespace;
+
+pub use events::{
+    Event,
+    Events,
+    Handler
+};
+
+pub use formatter::Formatter;
+
+pub use minifier::Minifier;
+
+pub use position::{
+    Position,
+    Span
+};
 
+pub use selector::Selector;
 
-fn unexpected_leaf(leaf: Option<Leaf>, expected: &str) -> !
+pub use xpath::{
+    XPath,
+    XPathNode,
+    XPathValue
+};
+
+pub use xml::{
+    XmlDeclaration,
+    ProcessingInstruction
+};
+
+pub use serializer::{
+    Serializer,
+    Serialize
+};
+
+pub use syntaxer::{
+    Tag,
+    Text,
+    Quote,
+    ElementBody,
+    ElementEnd
+};
+
+mod error;
+mod attributes;
+mod doctype;
+mod dom;
+mod encoding;
+mod events;
+mod foreign;
+mod formatter;
+mod minifier;
+mod mutation;
+mod position;
+mod selector;
+mod serializer;
+pub mod syntaxer;
+mod tree_builder;
+mod xpath;
+mod xml;
+
+
+fn unexpected_leaf(leaf: Option<Leaf<'_>>, expected: &str) -> ParseError
 {
+    let line = match leaf
+    {
+        Some(Leaf::Body(ref x)) => Some(x.position.line),
+        Some(Leaf::End(ref x)) => Some(x.position.line),
+        _ => None
+    };
+
     let leaf = match leaf
     {
         Some(leaf) => format!("Leaf::{leaf:?}"),
         None => "none".to_owned()
     };
 
-    panic!("unexpected {}, expected Leaf::{}", leaf, expected)
+    ParseError::new(ParseErrorKind::UnexpectedLeaf, format!("Leaf::{expected}"), leaf, line)
 }
 
 #[derive(Debug)]
-pub enum Child
+pub enum Child<'a>
 {
-    Element(Element),
-    Text(String)
+    Element(Element<'a>),
+    Text(Text<'a>),
+    Comment(Comment<'a>),
+    // only xml documents have these, html turns them into comments
+    ProcessingInstruction(ProcessingInstruction<'a>)
 }
 
-impl Child
+impl<'a> Child<'a>
 {
-    pub fn parse(mut leaves: &mut Peekable<Syntaxer<'_>>) -> Option<Self>
+    pub fn parse(leaves: &mut Syntaxer<'a>) -> Result<Option<Self>, ParseError>
     {
-        Some(match leaves.peek()
+        Ok(Some(match leaves.peek()
         {
-            Some(Leaf::Content(_)) =>
+            Some(Ok(Leaf::Content(_))) =>
             {
                 let text = match leaves.next()
                 {
-                    Some(Leaf::Content(text)) => text,
+                    Some(Ok(Leaf::Content(text))) => text,
                     _ => unreachable!()
                 };
 
-                if text.trim().is_empty()
+                Self::Text(text)
+            },
+            Some(Ok(Leaf::Comment(..))) =>
+            {
+                match leaves.next()
                 {
-                    return None;
+                    Some(Ok(Leaf::Comment(text, span))) => Self::Comment(Comment{text: Cow::Borrowed(text), span}),
+                    _ => unreachable!()
                 }
+            },
+            Some(Ok(Leaf::Doctype(_))) =>
+            {
+                // a doctype anywhere but the start means nothing
+                leaves.next();
 
-                Self::Text(text)
+                return Ok(None);
             },
-            Some(Leaf::Body(_)) => Self::Element(Element::parse(&mut leaves)),
-            leaf => unexpected_leaf(leaf.cloned(), "{Content, Body}")
-        })
+            Some(Ok(Leaf::Body(_))) => Self::Element(Element::parse(leaves)?),
+            _ =>
+            {
+                let leaf = leaves.next().transpose()?;
+
+                return Err(unexpected_leaf(leaf, "{Content, Comment, Body}"));
+            }
+        }))
     }
 
-    pub fn element(&self) -> Option<&Element>
+    pub fn element(&self) -> Option<&Element<'a>>
     {
         match self
         {
@@ -70,93 +203,127 @@ impl Child
     {
         match self
         {
-            Child::Text(ref text) => Some(text),
+            Child::Text(ref text) => Some(text.content()),
+            _ => None
+        }
+    }
+
+    pub fn raw_text(&self) -> Option<&str>
+    {
+        match self
+        {
+            Child::Text(ref text) => Some(text.raw()),
             _ => None
         }
     }
+
+    pub fn comment(&self) -> Option<&str>
+    {
+        match self
+        {
+            Child::Comment(ref comment) => Some(comment.text()),
+            _ => None
+        }
+    }
+
+    pub fn is_comment(&self) -> bool
+    {
+        self.comment().is_some()
+    }
+
+    pub fn processing_instruction(&self) -> Option<&ProcessingInstruction<'a>>
+    {
+        match self
+        {
+            Child::ProcessingInstruction(ref instruction) => Some(instruction),
+            _ => None
+        }
+    }
+
+    pub fn span(&self) -> Option<Span>
+    {
+        match self
+        {
+            Child::Element(ref element) => Some(element.span()),
+            Child::Text(ref text) => Some(text.span()),
+            Child::Comment(ref comment) => Some(comment.span()),
+            Child::ProcessingInstruction(ref instruction) => Some(instruction.span())
+        }
+    }
+
+    pub fn into_owned(self) -> Child<'static>
+    {
+        match self
+        {
+            Child::Element(element) => Child::Element(element.into_owned()),
+            Child::Text(text) => Child::Text(text.into_owned()),
+            Child::Comment(comment) => Child::Comment(comment.into_owned()),
+            Child::ProcessingInstruction(instruction) =>
+            {
+                Child::ProcessingInstruction(instruction.into_owned())
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
-pub struct Element
+// whats between <!-- and -->
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment<'a>
 {
-    name: String,
-    tags: Box<[Tag]>,
-    children: Box<[Child]>
+    text: Cow<'a, str>,
+    // from <!-- to -->
+    span: Span
 }
 
-impl Element
+impl<'a> Comment<'a>
 {
-    pub fn parse(mut leaves: &mut Peekable<Syntaxer<'_>>) -> Self
+    pub fn new(text: impl Into<Cow<'a, str>>) -> Self
     {
-        let body = match leaves.next()
-        {
-            Some(Leaf::Body(body)) => body,
-            leaf => unexpected_leaf(leaf, "Body")
-        };
+        Self{text: text.into(), span: Span::default()}
+    }
 
-        let mut children = Vec::new();
-
-        let childless = [
-            "area",
-            "base",
-            "br",
-            "col",
-            "command",
-            "embed",
-            "hr",
-            "img",
-            "input",
-            "keygen",
-            "link",
-            "meta",
-            "param",
-            "source",
-            "track",
-            "wbr"
-        ];
-
-        let has_children = !childless.contains(&body.name.as_ref());
-
-        if has_children
-        {
-            while let Some(leaf) = leaves.peek()
-            {
-                match leaf
-                {
-                    Leaf::End(end) =>
-                    {
-                        if end.name != body.name
-                        {
-                            panic!(
-                                "expected {} end (line {}), got {} end (line {})",
-                                body.name,
-                                body.line,
-                                end.name,
-                                end.line
-                            );
-                        }
-
-                        leaves.next();
-
-                        break;
-                    },
-                    _ => ()
-                }
+    pub fn text(&self) -> &str
+    {
+        &self.text
+    }
 
-                if let Some(child) = Child::parse(&mut leaves)
-                {
-                    children.push(child);
-                }
-            }
-        }
+    pub fn span(&self) -> Span
+    {
+        self.span
+    }
 
-        let children = children.into_boxed_slice();
+    pub fn into_owned(self) -> Comment<'static>
+    {
+        Comment{text: Cow::Owned(self.text.into_owned()), span: self.span}
+    }
+}
 
-        Self{name: body.name, tags: body.tags, children}
+#[derive(Debug)]
+pub struct Element<'a>
+{
+    name: Cow<'a, str>,
+    // how the name was written in the text if thats different
+    spelling: Option<Cow<'a, str>>,
+    namespace: Namespace,
+    // only elements from xml documents have one, any uri can be a namespace there
+    namespace_uri: Option<Cow<'a, str>>,
+    span: Span,
+    tags: Attributes<'a>,
+    children: Vec<Child<'a>>,
+    // changed through the mutation api since it was parsed
+    is_modified: bool,
+    // doesnt match its span in the text exactly because of misnesting
+    is_reconstructed: bool
+}
+
+impl<'a> Element<'a>
+{
+    pub fn parse(leaves: &mut Syntaxer<'a>) -> Result<Self, ParseError>
+    {
+        TreeBuilder::new().parse_element(leaves)
     }
 
-    #[allow(dead_code)]
-    pub fn get_name(&self, name: &str) -> Option<&Element>
+    pub fn get_name(&self, name: &str) -> Option<&Element<'a>>
     {
         self.children.iter().find(|child|
         {
@@ -178,8 +345,7 @@ impl Element
         })
     }
 
-    #[allow(dead_code)]
-    pub fn get(&self, index: usize) -> Option<&Child>
+    pub fn get(&self, index: usize) -> Option<&Child<'a>>
     {
         self.children.get(index)
     }
@@ -189,20 +355,108 @@ impl Element
         &self.name
     }
 
-    pub fn tags(&self) -> &[Tag]
+    // the name like it was written, the same as name if it wasnt in the text
+    pub fn original_name(&self) -> &str
+    {
+        self.spelling.as_deref().unwrap_or(&self.name)
+    }
+
+    // html unless its inside of svg or math
+    pub fn namespace(&self) -> Namespace
+    {
+        self.namespace
+    }
+
+    // the uri of namespace, xml elements without a namespace have an empty one
+    pub fn namespace_uri(&self) -> &str
+    {
+        self.namespace_uri.as_deref().unwrap_or_else(|| self.namespace.uri())
+    }
+
+    // the part before the colon in xml names like atom:link
+    pub fn prefix(&self) -> Option<&str>
+    {
+        self.name.split_once(':').map(|(prefix, _)| prefix)
+    }
+
+    pub fn local_name(&self) -> &str
     {
-        &self.tags
+        self.name.split_once(':').map(|(_, local)| local).unwrap_or(&self.name)
     }
 
-    pub fn children(&self) -> &[Child]
+    pub fn is_xml(&self) -> bool
+    {
+        self.namespace_uri.is_some()
+    }
+
+    // void html elements like br, in xml anything can have children
+    pub(super) fn is_childless(&self) -> bool
+    {
+        !self.is_xml() && is_childless(&self.name)
+    }
+
+    // html names dont care about case but svg, mathml and xml ones do
+    pub(super) fn is_named(&self, name: &str) -> bool
+    {
+        if self.namespace.is_foreign()
+        {
+            self.name == name
+        } else
+        {
+            self.name.eq_ignore_ascii_case(name)
+        }
+    }
+
+    // from the start tag up to the end tag, or the last child if it wasnt closed explicitly
+    pub fn span(&self) -> Span
+    {
+        self.span
+    }
+
+    // whether it or anything inside of it was changed since it was parsed
+    pub fn is_modified(&self) -> bool
+    {
+        self.is_modified || self.children.iter().any(|child|
+        {
+            child.element().is_some_and(Element::is_modified)
+        })
+    }
+
+    pub fn tags(&self) -> &[Tag<'a>]
+    {
+        self.tags.as_slice()
+    }
+
+    pub fn children(&self) -> &[Child<'a>]
     {
         &self.children
     }
+
+    // copies whatever is still borrowed from the text
+    pub fn into_owned(self) -> Element<'static>
+    {
+        Element{
+            name: Cow::Owned(self.name.into_owned()),
+            spelling: self.spelling.map(|spelling| Cow::Owned(spelling.into_owned())),
+            namespace: self.namespace,
+            namespace_uri: self.namespace_uri.map(|uri| Cow::Owned(uri.into_owned())),
+            span: self.span,
+            tags: self.tags.into_owned(),
+            children: self.children.into_iter().map(Child::into_owned).collect(),
+            is_modified: self.is_modified,
+            is_reconstructed: self.is_reconstructed
+        }
+    }
+
+    pub fn comments(&self) -> impl Iterator<Item=&str>
+    {
+        self.children.iter().filter_map(|child| child.comment())
+    }
 }
 
-impl Index<usize> for Element
+impl<'a> Index<usize> for Element<'a>
 {
-    type Output = Child;
+    type Output = Child<'a>;
 
     fn index(&self, index: usize) -> &Self::Output
     {
@@ -210,24 +464,294 @@ impl Index<usize> for Element
     }
 }
 
+#[derive(Debug)]
+pub struct Document<'a>
+{
+    doctype: Option<Doctype>,
+    // the <?xml ?> at the start of xml documents
+    declaration: Option<XmlDeclaration>,
+    // only known when parsing from bytes
+    encoding: Option<(Encoding, EncodingSource)>,
+    children: Vec<Child<'a>>,
+    diagnostics: Box<[Diagnostic]>,
+    is_modified: bool
+}
+
+impl<'a> Document<'a>
+{
+    pub fn parse(leaves: &mut Syntaxer<'a>) -> Result<Self, ParseError>
+    {
+        let mut doctype = None;
+        let mut builder = TreeBuilder::new();
+
+        for leaf in leaves.by_ref()
+        {
+            match leaf?
+            {
+                Leaf::Doctype(x) if doctype.is_none() && !builder.has_elements() =>
+                {
+                    doctype = Some(x);
+                },
+                leaf => builder.process(leaf)
+            }
+        }
+
+        let (children, mut diagnostics) = builder.finish();
+
+        diagnostics.append(&mut leaves.take_diagnostics());
+        diagnostics.sort_by_key(|diagnostic| diagnostic.position());
+
+        let diagnostics = diagnostics.into_boxed_slice();
+
+        Ok(Self{doctype, declaration: None, encoding: None, children, diagnostics, is_modified: false})
+    }
+
+    pub fn doctype(&self) -> Option<&Doctype>
+    {
+        self.doctype.as_ref()
+    }
+
+    pub fn declaration(&self) -> Option<&XmlDeclaration>
+    {
+        self.declaration.as_ref()
+    }
+
+    // parsed with Parser::parse_xml, xml documents always have a root element
+    pub fn is_xml(&self) -> bool
+    {
+        self.root().is_some_and(Element::is_xml)
+    }
+
+    pub fn encoding(&self) -> Option<Encoding>
+    {
+        self.encoding.map(|(encoding, _)| encoding)
+    }
+
+    pub fn encoding_source(&self) -> Option<EncodingSource>
+    {
+        self.encoding.map(|(_, source)| source)
+    }
+
+    pub fn quirks_mode(&self) -> QuirksMode
+    {
+        self.doctype.as_ref().map(|doctype| doctype.quirks_mode()).unwrap_or(QuirksMode::Quirks)
+    }
+
+    pub fn children(&self) -> &[Child<'a>]
+    {
+        &self.children
+    }
+
+    pub fn into_owned(self) -> Document<'static>
+    {
+        Document{
+            doctype: self.doctype,
+            declaration: self.declaration,
+            encoding: self.encoding,
+            children: self.children.into_iter().map(Child::into_owned).collect(),
+            diagnostics: self.diagnostics,
+            is_modified: self.is_modified
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic]
+    {
+        &self.diagnostics
+    }
+
+    pub fn is_modified(&self) -> bool
+    {
+        self.is_modified
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item=&Element<'a>>
+    {
+        self.children.iter().filter_map(|child| child.element())
+    }
+
+    pub fn root(&self) -> Option<&Element<'a>>
+    {
+        self.elements().next()
+    }
+}
+
+enum Source<'a>
+{
+    Text(TextIterInner<'a>),
+    // gets decoded once parsing starts so the hint can still change
+    Bytes(Cow<'a, [u8]>),
+    Decoded(String, Encoding, EncodingSource)
+}
+
 pub struct Parser<'a>
 {
-    syntaxer: Syntaxer<'a>
+    source: Source<'a>,
+    encoding_hint: Option<Encoding>,
+    skip_comments: bool,
+    decode_entities: bool
 }
 
 impl<'a> Parser<'a>
 {
     pub fn new(text: TextIterInner<'a>) -> Self
     {
-        let syntaxer = Syntaxer::new(text);
+        Self::with_source(Source::Text(text))
+    }
 
-        Self{syntaxer}
+    pub fn from_bytes(bytes: &'a [u8]) -> Self
+    {
+        Self::with_source(Source::Bytes(Cow::Borrowed(bytes)))
     }
 
-    pub fn parse(self) -> Element
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self>
     {
-        let mut leaves = self.syntaxer.peekable();
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Ok(Self::with_source(Source::Bytes(Cow::Owned(bytes))))
+    }
+
+    fn with_source(source: Source<'a>) -> Self
+    {
+        Self{
+            source,
+            encoding_hint: None,
+            skip_comments: false,
+            decode_entities: true
+        }
+    }
+
+    // the encoding from somewhere outside of the document, like a content-type header
+    // only a byte order mark overrides it
+    pub fn encoding_hint(mut self, encoding: Encoding) -> Self
+    {
+        self.encoding_hint = Some(encoding);
+
+        self
+    }
+
+    pub fn skip_comments(mut self, skip: bool) -> Self
+    {
+        self.skip_comments = skip;
+
+        self
+    }
+
+    pub fn decode_entities(mut self, decode: bool) -> Self
+    {
+        self.decode_entities = decode;
+
+        self
+    }
+
+    // borrows from the text when given one, decoded bytes only live as long as
+    // the parser so those documents get copied out of it
+    pub fn parse(mut self) -> Result<Document<'a>, ParseError>
+    {
+        self.decode();
+
+        match &self.source
+        {
+            Source::Text(text) => Document::parse(&mut self.syntaxer_for(text.clone())),
+            Source::Decoded(text, encoding, source) =>
+            {
+                let mut document = Document::parse(&mut self.syntaxer_for(text.chars()))?.into_owned();
+                document.encoding = Some((*encoding, *source));
+
+                Ok(document)
+            },
+            Source::Bytes(_) => unreachable!()
+        }
+    }
+
+    // strict xml like feeds and sitemaps, anything thats not well formed is an error
+    // and nothing gets recovered from, the tree is the same as for html
+    pub fn parse_xml(mut self) -> Result<Document<'a>, XmlError>
+    {
+        self.decode_with(Encoding::detect_xml);
+
+        match &self.source
+        {
+            Source::Text(text) => self.xml_parser(text.as_str()).parse(),
+            Source::Decoded(text, encoding, source) =>
+            {
+                let mut document = self.xml_parser(text).parse()?.into_owned();
+                document.encoding = Some((*encoding, *source));
+
+                Ok(document)
+            },
+            Source::Bytes(_) => unreachable!()
+        }
+    }
+
+    // the same tree but with parent and sibling links
+    pub fn parse_dom(self) -> Result<Dom<'a>, ParseError>
+    {
+        self.parse().map(Dom::from)
+    }
+
+    pub fn events(&mut self) -> Events<'_>
+    {
+        let (syntaxer, encoding) = self.syntaxer();
+
+        Events::new(syntaxer, encoding)
+    }
+
+    // feeds every event to the handler, returns what got recovered from
+    pub fn stream(&mut self, handler: &mut impl Handler) -> Result<Vec<Diagnostic>, ParseError>
+    {
+        let mut events = self.events();
+        events.handle(handler)?;
+
+        Ok(events.take_diagnostics())
+    }
+
+    fn decode(&mut self)
+    {
+        self.decode_with(Encoding::detect);
+    }
+
+    fn decode_with(&mut self, detect: fn(&[u8], Option<Encoding>) -> (Encoding, EncodingSource))
+    {
+        if let Source::Bytes(bytes) = &self.source
+        {
+            let (encoding, source) = detect(bytes, self.encoding_hint);
+
+            self.source = Source::Decoded(encoding.decode(bytes), encoding, source);
+        }
+    }
+
+    fn syntaxer(&mut self) -> (Syntaxer<'_>, Option<(Encoding, EncodingSource)>)
+    {
+        self.decode();
+
+        let (text, encoding) = match &self.source
+        {
+            Source::Text(text) => (text.clone(), None),
+            Source::Decoded(text, encoding, source) => (text.chars(), Some((*encoding, *source))),
+            Source::Bytes(_) => unreachable!()
+        };
+
+        (self.syntaxer_for(text), encoding)
+    }
+
+    fn syntaxer_for<'b>(&self, text: TextIterInner<'b>) -> Syntaxer<'b>
+    {
+        let mut syntaxer = Syntaxer::new(text);
+
+        syntaxer.skip_comments(self.skip_comments);
+        syntaxer.decode_entities(self.decode_entities);
+
+        syntaxer
+    }
+
+    fn xml_parser<'b>(&self, text: &'b str) -> XmlParser<'b>
+    {
+        let mut parser = XmlParser::new(text);
+
+        parser.skip_comments(self.skip_comments);
+        parser.decode_entities(self.decode_entities);
 
-        Element::parse(&mut leaves)
+        parser
     }
 }
@@ -0,0 +1,256 @@
+use std::{
+    mem,
+    slice
+};
+
+use super::{
+    Element,
+    Tag,
+    Namespace
+};
+
+
+// only being there matters, the value doesnt
+const BOOLEAN_ATTRIBUTES: [&str; 25] = [
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected"
+];
+
+pub(super) fn is_boolean_attribute(name: &str) -> bool
+{
+    BOOLEAN_ATTRIBUTES.iter().any(|x| x.eq_ignore_ascii_case(name))
+}
+
+// the tags of an element in the order they were written, names dont care about case except in xml
+// and theres only ever one of each, elements rarely have enough of them for hashing to beat a scan
+#[derive(Debug, Clone, Default)]
+pub struct Attributes<'a>
+{
+    tags: Vec<Tag<'a>>,
+    is_case_sensitive: bool
+}
+
+impl<'a> Attributes<'a>
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    // later tags with a name thats already there get dropped, the first one wins like in browsers
+    pub fn from_tags(tags: impl IntoIterator<Item=Tag<'a>>) -> Self
+    {
+        let mut attributes = Self::new();
+
+        for tag in tags
+        {
+            if !attributes.contains(tag.name())
+            {
+                attributes.tags.push(tag);
+            }
+        }
+
+        attributes
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tag<'a>>
+    {
+        self.tags.iter().find(|tag| self.is_named(tag, name))
+    }
+
+    // tags without a value give an empty string
+    pub fn value(&self, name: &str) -> Option<&str>
+    {
+        self.get(name).map(|tag| tag.content().unwrap_or_default())
+    }
+
+    // for namespaced tags like xlink:href, the prefix doesnt matter only what it stands for
+    pub fn get_ns(&self, namespace: Namespace, local_name: &str) -> Option<&Tag<'a>>
+    {
+        self.tags.iter().find(|tag| tag.namespace() == Some(namespace) && tag.local_name() == local_name)
+    }
+
+    // like get_ns but for any namespace, xml documents can have their own
+    pub fn get_ns_uri(&self, uri: &str, local_name: &str) -> Option<&Tag<'a>>
+    {
+        self.tags.iter().find(|tag| tag.namespace_uri() == Some(uri) && tag.local_name() == local_name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool
+    {
+        self.get(name).is_some()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.tags.is_empty()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Tag<'a>>
+    {
+        self.tags.iter()
+    }
+
+    pub fn as_slice(&self) -> &[Tag<'a>]
+    {
+        &self.tags
+    }
+
+    // replaces the tag with the same name in place if theres one, returns the old one
+    pub fn set(&mut self, tag: Tag<'a>) -> Option<Tag<'a>>
+    {
+        let index = self.tags.iter().position(|other| self.is_named(other, tag.name()));
+
+        match index.map(|index| &mut self.tags[index])
+        {
+            Some(other) => Some(mem::replace(other, tag)),
+            None =>
+            {
+                self.tags.push(tag);
+
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Tag<'a>>
+    {
+        let index = self.tags.iter().position(|tag| self.is_named(tag, name))?;
+
+        Some(self.tags.remove(index))
+    }
+
+    pub fn into_owned(self) -> Attributes<'static>
+    {
+        Attributes{
+            tags: self.tags.into_iter().map(Tag::into_owned).collect(),
+            is_case_sensitive: self.is_case_sensitive
+        }
+    }
+
+    // xml names care about case so the tags r taken as they r and looked up exactly
+    pub(super) fn from_vec(tags: Vec<Tag<'a>>) -> Self
+    {
+        Self{tags, is_case_sensitive: true}
+    }
+
+    fn is_named(&self, tag: &Tag<'_>, name: &str) -> bool
+    {
+        if self.is_case_sensitive
+        {
+            tag.name() == name
+        } else
+        {
+            tag.name().eq_ignore_ascii_case(name)
+        }
+    }
+}
+
+impl<'a> FromIterator<Tag<'a>> for Attributes<'a>
+{
+    fn from_iter<I: IntoIterator<Item=Tag<'a>>>(iter: I) -> Self
+    {
+        Self::from_tags(iter)
+    }
+}
+
+impl<'b, 'a> IntoIterator for &'b Attributes<'a>
+{
+    type Item = &'b Tag<'a>;
+    type IntoIter = slice::Iter<'b, Tag<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for Attributes<'a>
+{
+    type Item = Tag<'a>;
+    type IntoIter = std::vec::IntoIter<Tag<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.tags.into_iter()
+    }
+}
+
+impl<'a> Element<'a>
+{
+    pub fn attributes(&self) -> &Attributes<'a>
+    {
+        &self.tags
+    }
+
+    // the value of a tag, tags without a value give an empty string
+    pub fn attr(&self, name: &str) -> Option<&str>
+    {
+        self.tags.value(name)
+    }
+
+    pub fn attr_ns(&self, namespace: Namespace, local_name: &str) -> Option<&str>
+    {
+        self.tags.get_ns(namespace, local_name).map(|tag| tag.content().unwrap_or_default())
+    }
+
+    pub fn attr_ns_uri(&self, uri: &str, local_name: &str) -> Option<&str>
+    {
+        self.tags.get_ns_uri(uri, local_name).map(|tag| tag.content().unwrap_or_default())
+    }
+
+    pub fn has_attr(&self, name: &str) -> bool
+    {
+        self.tags.contains(name)
+    }
+
+    pub fn id(&self) -> Option<&str>
+    {
+        self.attr("id")
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item=&str>
+    {
+        self.attr("class").unwrap_or_default().split_ascii_whitespace()
+    }
+
+    pub fn has_class(&self, class: &str) -> bool
+    {
+        self.classes().any(|x| x == class)
+    }
+
+    // for things like disabled or checked where being there at all means its on,
+    // even disabled="false" is still disabled
+    pub fn is_attr_set(&self, name: &str) -> bool
+    {
+        is_boolean_attribute(name) && self.has_attr(name)
+    }
+}
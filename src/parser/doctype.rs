@@ -0,0 +1,241 @@
+use std::{
+    str::Chars,
+    iter::Peekable
+};
+
+
+const QUIRKS_PUBLIC_IDS: [&str; 3] = [
+    "-//W3O//DTD W3 HTML Strict 3.0//EN//",
+    "-/W3C/DTD HTML 4.0 Transitional/EN",
+    "HTML"
+];
+
+const QUIRKS_SYSTEM_ID: &str = "http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd";
+
+const QUIRKS_PUBLIC_PREFIXES: [&str; 55] = [
+    "+//Silmaril//dtd html Pro v0r11 19970101//",
+    "-//AS//DTD HTML 3.0 asWedit + extensions//",
+    "-//AdvaSoft Ltd//DTD HTML 3.0 asWedit + extensions//",
+    "-//IETF//DTD HTML 2.0 Level 1//",
+    "-//IETF//DTD HTML 2.0 Level 2//",
+    "-//IETF//DTD HTML 2.0 Strict Level 1//",
+    "-//IETF//DTD HTML 2.0 Strict Level 2//",
+    "-//IETF//DTD HTML 2.0 Strict//",
+    "-//IETF//DTD HTML 2.0//",
+    "-//IETF//DTD HTML 2.1E//",
+    "-//IETF//DTD HTML 3.0//",
+    "-//IETF//DTD HTML 3.2 Final//",
+    "-//IETF//DTD HTML 3.2//",
+    "-//IETF//DTD HTML 3//",
+    "-//IETF//DTD HTML Level 0//",
+    "-//IETF//DTD HTML Level 1//",
+    "-//IETF//DTD HTML Level 2//",
+    "-//IETF//DTD HTML Level 3//",
+    "-//IETF//DTD HTML Strict Level 0//",
+    "-//IETF//DTD HTML Strict Level 1//",
+    "-//IETF//DTD HTML Strict Level 2//",
+    "-//IETF//DTD HTML Strict Level 3//",
+    "-//IETF//DTD HTML Strict//",
+    "-//IETF//DTD HTML//",
+    "-//Metrius//DTD Metrius Presentational//",
+    "-//Microsoft//DTD Internet Explorer 2.0 HTML Strict//",
+    "-//Microsoft//DTD Internet Explorer 2.0 HTML//",
+    "-//Microsoft//DTD Internet Explorer 2.0 Tables//",
+    "-//Microsoft//DTD Internet Explorer 3.0 HTML Strict//",
+    "-//Microsoft//DTD Internet Explorer 3.0 HTML//",
+    "-//Microsoft//DTD Internet Explorer 3.0 Tables//",
+    "-//Netscape Comm. Corp.//DTD HTML//",
+    "-//Netscape Comm. Corp.//DTD Strict HTML//",
+    "-//O'Reilly and Associates//DTD HTML 2.0//",
+    "-//O'Reilly and Associates//DTD HTML Extended 1.0//",
+    "-//O'Reilly and Associates//DTD HTML Extended Relaxed 1.0//",
+    "-//SQ//DTD HTML 2.0 HoTMetaL + extensions//",
+    "-//SoftQuad Software//DTD HoTMetaL PRO 6.0::19990601::extensions to HTML 4.0//",
+    "-//SoftQuad//DTD HoTMetaL PRO 4.0::19970916::extensions to HTML 4.0//",
+    "-//Spyglass//DTD HTML 2.0 Extended//",
+    "-//Sun Microsystems Corp.//DTD HotJava HTML//",
+    "-//Sun Microsystems Corp.//DTD HotJava Strict HTML//",
+    "-//W3C//DTD HTML 3 1995-03-24//",
+    "-//W3C//DTD HTML 3.2 Draft//",
+    "-//W3C//DTD HTML 3.2 Final//",
+    "-//W3C//DTD HTML 3.2//",
+    "-//W3C//DTD HTML 3.2S Draft//",
+    "-//W3C//DTD HTML 4.0 Frameset//",
+    "-//W3C//DTD HTML 4.0 Transitional//",
+    "-//W3C//DTD HTML Experimental 19960712//",
+    "-//W3C//DTD HTML Experimental 970421//",
+    "-//W3C//DTD W3 HTML//",
+    "-//W3O//DTD W3 HTML 3.0//",
+    "-//WebTechs//DTD Mozilla HTML 2.0//",
+    "-//WebTechs//DTD Mozilla HTML//"
+];
+
+// quirks without a system id, limited quirks with one
+const HTML4_PUBLIC_PREFIXES: [&str; 2] = [
+    "-//W3C//DTD HTML 4.01 Frameset//",
+    "-//W3C//DTD HTML 4.01 Transitional//"
+];
+
+const LIMITED_QUIRKS_PUBLIC_PREFIXES: [&str; 2] = [
+    "-//W3C//DTD XHTML 1.0 Frameset//",
+    "-//W3C//DTD XHTML 1.0 Transitional//"
+];
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool
+{
+    text.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuirksMode
+{
+    NoQuirks,
+    LimitedQuirks,
+    Quirks
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Doctype
+{
+    name: Option<String>,
+    public_id: Option<String>,
+    system_id: Option<String>,
+    force_quirks: bool
+}
+
+impl Doctype
+{
+    // parses everything between <!DOCTYPE and >
+    pub fn parse(text: &str) -> Self
+    {
+        let mut text = text.chars().peekable();
+
+        let mut this = Self{
+            name: None,
+            public_id: None,
+            system_id: None,
+            force_quirks: false
+        };
+
+        this.name = Self::parse_word(&mut text).map(|name| name.to_ascii_lowercase());
+
+        if this.name.is_none()
+        {
+            this.force_quirks = true;
+
+            return this;
+        }
+
+        let keyword = Self::parse_word(&mut text).map(|word| word.to_ascii_lowercase());
+        match keyword.as_deref()
+        {
+            None => (),
+            Some("public") =>
+            {
+                this.public_id = Self::parse_quoted(&mut text);
+                this.system_id = Self::parse_quoted(&mut text);
+
+                this.force_quirks = this.public_id.is_none();
+            },
+            Some("system") =>
+            {
+                this.system_id = Self::parse_quoted(&mut text);
+
+                this.force_quirks = this.system_id.is_none();
+            },
+            Some(_) => this.force_quirks = true
+        }
+
+        this
+    }
+
+    fn skip_whitespace(text: &mut Peekable<Chars>)
+    {
+        while text.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn parse_word(text: &mut Peekable<Chars>) -> Option<String>
+    {
+        Self::skip_whitespace(text);
+
+        let mut word = String::new();
+        while let Some(c) = text.next_if(|c| !c.is_whitespace() && *c != '"' && *c != '\'')
+        {
+            word.push(c);
+        }
+
+        (!word.is_empty()).then_some(word)
+    }
+
+    fn parse_quoted(text: &mut Peekable<Chars>) -> Option<String>
+    {
+        Self::skip_whitespace(text);
+
+        let quote = text.next_if(|c| *c == '"' || *c == '\'')?;
+
+        Some(text.by_ref().take_while(|c| *c != quote).collect())
+    }
+
+    pub fn name(&self) -> Option<&str>
+    {
+        self.name.as_deref()
+    }
+
+    pub fn public_id(&self) -> Option<&str>
+    {
+        self.public_id.as_deref()
+    }
+
+    pub fn system_id(&self) -> Option<&str>
+    {
+        self.system_id.as_deref()
+    }
+
+    pub fn quirks_mode(&self) -> QuirksMode
+    {
+        if self.force_quirks || self.name() != Some("html")
+        {
+            return QuirksMode::Quirks;
+        }
+
+        if let Some(system_id) = self.system_id()
+        {
+            if system_id.eq_ignore_ascii_case(QUIRKS_SYSTEM_ID)
+            {
+                return QuirksMode::Quirks;
+            }
+        }
+
+        let public_id = match self.public_id()
+        {
+            Some(x) => x,
+            None => return QuirksMode::NoQuirks
+        };
+
+        let is_quirks = QUIRKS_PUBLIC_IDS.iter().any(|id| public_id.eq_ignore_ascii_case(id))
+            || QUIRKS_PUBLIC_PREFIXES.iter().any(|prefix| starts_with_ignore_case(public_id, prefix));
+
+        let is_html4 = HTML4_PUBLIC_PREFIXES.iter().any(|prefix|
+        {
+            starts_with_ignore_case(public_id, prefix)
+        });
+
+        if is_quirks || (is_html4 && self.system_id.is_none())
+        {
+            return QuirksMode::Quirks;
+        }
+
+        let is_limited = LIMITED_QUIRKS_PUBLIC_PREFIXES.iter().any(|prefix|
+        {
+            starts_with_ignore_case(public_id, prefix)
+        });
+
+        if is_limited || is_html4
+        {
+            QuirksMode::LimitedQuirks
+        } else
+        {
+            QuirksMode::NoQuirks
+        }
+    }
+}
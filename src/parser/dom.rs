@@ -0,0 +1,561 @@
+use std::{
+    borrow::Cow,
+    ops::Index
+};
+
+use super::{
+    Child,
+    Comment,
+    Attributes,
+    Element,
+    Document,
+    Doctype,
+    QuirksMode,
+    Encoding,
+    EncodingSource,
+    Diagnostic,
+    Span,
+    Tag,
+    Text,
+    Namespace,
+    ProcessingInstruction
+};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId
+{
+    pub fn index(&self) -> usize
+    {
+        self.0
+    }
+}
+
+#[derive(Debug)]
+pub enum NodeKind<'a>
+{
+    // the node everything at the top level hangs off of
+    Document,
+    // the same things an Element has except for the children
+    Element{
+        name: Cow<'a, str>,
+        // how the name was written in the text if thats different
+        spelling: Option<Cow<'a, str>>,
+        namespace: Namespace,
+        // only elements from xml documents have one
+        namespace_uri: Option<Cow<'a, str>>,
+        span: Span,
+        tags: Attributes<'a>,
+        is_modified: bool,
+        is_reconstructed: bool
+    },
+    Text(Text<'a>),
+    Comment(Comment<'a>),
+    ProcessingInstruction(ProcessingInstruction<'a>)
+}
+
+#[derive(Debug)]
+pub struct Node<'a>
+{
+    kind: NodeKind<'a>,
+    parent: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    prev_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>
+}
+
+impl<'a> Node<'a>
+{
+    fn new(kind: NodeKind<'a>) -> Self
+    {
+        Self{
+            kind,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            prev_sibling: None,
+            next_sibling: None
+        }
+    }
+
+    pub fn kind(&self) -> &NodeKind<'a>
+    {
+        &self.kind
+    }
+
+    pub fn is_element(&self) -> bool
+    {
+        matches!(self.kind, NodeKind::Element{..})
+    }
+
+    pub fn name(&self) -> Option<&str>
+    {
+        match &self.kind
+        {
+            NodeKind::Element{name, ..} => Some(name),
+            _ => None
+        }
+    }
+
+    pub fn original_name(&self) -> Option<&str>
+    {
+        match &self.kind
+        {
+            NodeKind::Element{name, spelling, ..} => Some(spelling.as_deref().unwrap_or(name)),
+            _ => None
+        }
+    }
+
+    pub fn namespace(&self) -> Option<Namespace>
+    {
+        match &self.kind
+        {
+            NodeKind::Element{namespace, ..} => Some(*namespace),
+            _ => None
+        }
+    }
+
+    pub fn namespace_uri(&self) -> Option<&str>
+    {
+        match &self.kind
+        {
+            NodeKind::Element{namespace, namespace_uri, ..} =>
+            {
+                Some(namespace_uri.as_deref().unwrap_or_else(|| namespace.uri()))
+            },
+            _ => None
+        }
+    }
+
+    pub fn tags(&self) -> &[Tag<'a>]
+    {
+        match &self.kind
+        {
+            NodeKind::Element{tags, ..} => tags.as_slice(),
+            _ => &[]
+        }
+    }
+
+    pub fn attributes(&self) -> Option<&Attributes<'a>>
+    {
+        match &self.kind
+        {
+            NodeKind::Element{tags, ..} => Some(tags),
+            _ => None
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str>
+    {
+        self.attributes()?.value(name)
+    }
+
+    // doesnt match its span in the text exactly because of misnesting
+    pub fn is_reconstructed(&self) -> bool
+    {
+        matches!(self.kind, NodeKind::Element{is_reconstructed: true, ..})
+    }
+
+    pub fn is_modified(&self) -> bool
+    {
+        matches!(self.kind, NodeKind::Element{is_modified: true, ..})
+    }
+
+    pub fn text(&self) -> Option<&str>
+    {
+        match &self.kind
+        {
+            NodeKind::Text(text) => Some(text.content()),
+            _ => None
+        }
+    }
+
+    pub fn raw_text(&self) -> Option<&str>
+    {
+        match &self.kind
+        {
+            NodeKind::Text(text) => Some(text.raw()),
+            _ => None
+        }
+    }
+
+    pub fn comment(&self) -> Option<&str>
+    {
+        match &self.kind
+        {
+            NodeKind::Comment(comment) => Some(comment.text()),
+            _ => None
+        }
+    }
+
+    pub fn processing_instruction(&self) -> Option<&ProcessingInstruction<'a>>
+    {
+        match &self.kind
+        {
+            NodeKind::ProcessingInstruction(instruction) => Some(instruction),
+            _ => None
+        }
+    }
+
+    // same as Child::span, the document node doesnt have one
+    pub fn span(&self) -> Option<Span>
+    {
+        match &self.kind
+        {
+            NodeKind::Element{span, ..} => Some(*span),
+            NodeKind::Text(text) => Some(text.span()),
+            NodeKind::Comment(comment) => Some(comment.span()),
+            NodeKind::ProcessingInstruction(instruction) => Some(instruction.span()),
+            NodeKind::Document => None
+        }
+    }
+}
+
+// the same tree as a Document but flattened into one vec, so nodes know their parent and siblings
+#[derive(Debug)]
+pub struct Dom<'a>
+{
+    nodes: Vec<Node<'a>>,
+    doctype: Option<Doctype>,
+    encoding: Option<(Encoding, EncodingSource)>,
+    diagnostics: Box<[Diagnostic]>
+}
+
+impl<'a> Dom<'a>
+{
+    const DOCUMENT: NodeId = NodeId(0);
+
+    fn new() -> Self
+    {
+        Self{
+            nodes: vec![Node::new(NodeKind::Document)],
+            doctype: None,
+            encoding: None,
+            diagnostics: Box::new([])
+        }
+    }
+
+    // a dom with just this element under the document node
+    pub fn from_element(element: Element<'a>) -> Self
+    {
+        let mut dom = Self::new();
+        dom.append(Self::DOCUMENT, Child::Element(element));
+
+        dom
+    }
+
+    pub fn document(&self) -> NodeId
+    {
+        Self::DOCUMENT
+    }
+
+    pub fn doctype(&self) -> Option<&Doctype>
+    {
+        self.doctype.as_ref()
+    }
+
+    pub fn encoding(&self) -> Option<Encoding>
+    {
+        self.encoding.map(|(encoding, _)| encoding)
+    }
+
+    pub fn encoding_source(&self) -> Option<EncodingSource>
+    {
+        self.encoding.map(|(_, source)| source)
+    }
+
+    pub fn quirks_mode(&self) -> QuirksMode
+    {
+        self.doctype.as_ref().map(|doctype| doctype.quirks_mode()).unwrap_or(QuirksMode::Quirks)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic]
+    {
+        &self.diagnostics
+    }
+
+    // the first element at the top level
+    pub fn root(&self) -> Option<NodeId>
+    {
+        self.children(Self::DOCUMENT).find(|id| self[*id].is_element())
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node<'a>>
+    {
+        self.nodes.get(id.0)
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.nodes.len()
+    }
+
+    // theres always at least the document node
+    pub fn is_empty(&self) -> bool
+    {
+        self.nodes.len() == 1
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId>
+    {
+        self[id].parent
+    }
+
+    pub fn first_child(&self, id: NodeId) -> Option<NodeId>
+    {
+        self[id].first_child
+    }
+
+    pub fn last_child(&self, id: NodeId) -> Option<NodeId>
+    {
+        self[id].last_child
+    }
+
+    pub fn next_sibling(&self, id: NodeId) -> Option<NodeId>
+    {
+        self[id].next_sibling
+    }
+
+    pub fn prev_sibling(&self, id: NodeId) -> Option<NodeId>
+    {
+        self[id].prev_sibling
+    }
+
+    pub fn children(&self, id: NodeId) -> Siblings<'_, 'a>
+    {
+        Siblings{dom: self, next: self.first_child(id), is_forward: true}
+    }
+
+    pub fn following_siblings(&self, id: NodeId) -> Siblings<'_, 'a>
+    {
+        Siblings{dom: self, next: self.next_sibling(id), is_forward: true}
+    }
+
+    // nearest first
+    pub fn preceding_siblings(&self, id: NodeId) -> Siblings<'_, 'a>
+    {
+        Siblings{dom: self, next: self.prev_sibling(id), is_forward: false}
+    }
+
+    // from the parent up to the document node
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_, 'a>
+    {
+        Ancestors{dom: self, next: self.parent(id)}
+    }
+
+    // everything inside of the node in document order, not including itself
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_, 'a>
+    {
+        Descendants{dom: self, root: id, next: self.first_child(id)}
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item=NodeId> + '_
+    {
+        self.descendants(Self::DOCUMENT).filter(|id| self[*id].is_element())
+    }
+
+    fn push(&mut self, kind: NodeKind<'a>) -> NodeId
+    {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node::new(kind));
+
+        id
+    }
+
+    // deep documents would overflow the stack with recursion so theres one of its own here
+    fn append(&mut self, parent: NodeId, child: Child<'a>)
+    {
+        let mut stack = vec![(parent, vec![child].into_iter())];
+
+        loop
+        {
+            let (parent, child) = match stack.last_mut()
+            {
+                Some((parent, children)) => (*parent, children.next()),
+                None => return
+            };
+
+            let child = match child
+            {
+                Some(x) => x,
+                None =>
+                {
+                    stack.pop();
+
+                    continue;
+                }
+            };
+
+            let (kind, children) = match child
+            {
+                Child::Element(element) =>
+                {
+                    let kind = NodeKind::Element{
+                        name: element.name,
+                        spelling: element.spelling,
+                        namespace: element.namespace,
+                        namespace_uri: element.namespace_uri,
+                        span: element.span,
+                        tags: element.tags,
+                        is_modified: element.is_modified,
+                        is_reconstructed: element.is_reconstructed
+                    };
+
+                    (kind, element.children)
+                },
+                Child::Text(text) => (NodeKind::Text(text), Vec::new()),
+                Child::Comment(comment) => (NodeKind::Comment(comment), Vec::new()),
+                Child::ProcessingInstruction(instruction) =>
+                {
+                    (NodeKind::ProcessingInstruction(instruction), Vec::new())
+                }
+            };
+
+            let id = self.link(parent, kind);
+
+            if !children.is_empty()
+            {
+                stack.push((id, children.into_iter()));
+            }
+        }
+    }
+
+    // adds the node as the last child of parent
+    fn link(&mut self, parent: NodeId, kind: NodeKind<'a>) -> NodeId
+    {
+        let id = self.push(kind);
+
+        self.nodes[id.0].parent = Some(parent);
+
+        match self.nodes[parent.0].last_child
+        {
+            Some(last) =>
+            {
+                self.nodes[last.0].next_sibling = Some(id);
+                self.nodes[id.0].prev_sibling = Some(last);
+            },
+            None => self.nodes[parent.0].first_child = Some(id)
+        }
+
+        self.nodes[parent.0].last_child = Some(id);
+
+        id
+    }
+}
+
+impl<'a> From<Document<'a>> for Dom<'a>
+{
+    fn from(document: Document<'a>) -> Self
+    {
+        let mut dom = Self::new();
+
+        dom.doctype = document.doctype;
+        dom.encoding = document.encoding;
+        dom.diagnostics = document.diagnostics;
+
+        document.children.into_iter().for_each(|child|
+        {
+            dom.append(Self::DOCUMENT, child);
+        });
+
+        dom
+    }
+}
+
+impl<'a> Index<NodeId> for Dom<'a>
+{
+    type Output = Node<'a>;
+
+    fn index(&self, id: NodeId) -> &Self::Output
+    {
+        self.get(id).unwrap()
+    }
+}
+
+pub struct Siblings<'b, 'a>
+{
+    dom: &'b Dom<'a>,
+    next: Option<NodeId>,
+    is_forward: bool
+}
+
+impl<'b, 'a> Iterator for Siblings<'b, 'a>
+{
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let id = self.next?;
+
+        self.next = if self.is_forward
+        {
+            self.dom.next_sibling(id)
+        } else
+        {
+            self.dom.prev_sibling(id)
+        };
+
+        Some(id)
+    }
+}
+
+pub struct Ancestors<'b, 'a>
+{
+    dom: &'b Dom<'a>,
+    next: Option<NodeId>
+}
+
+impl<'b, 'a> Iterator for Ancestors<'b, 'a>
+{
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let id = self.next?;
+        self.next = self.dom.parent(id);
+
+        Some(id)
+    }
+}
+
+pub struct Descendants<'b, 'a>
+{
+    dom: &'b Dom<'a>,
+    root: NodeId,
+    next: Option<NodeId>
+}
+
+impl<'b, 'a> Iterator for Descendants<'b, 'a>
+{
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let id = self.next?;
+
+        // down if possible, otherwise the next sibling of the closest ancestor that has one
+        self.next = self.dom.first_child(id).or_else(||
+        {
+            let mut current = id;
+            loop
+            {
+                if current == self.root
+                {
+                    return None;
+                }
+
+                if let Some(sibling) = self.dom.next_sibling(current)
+                {
+                    return Some(sibling);
+                }
+
+                current = self.dom.parent(current)?;
+            }
+        });
+
+        Some(id)
+    }
+}
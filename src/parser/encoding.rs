@@ -0,0 +1,624 @@
+use std::{
+    fmt,
+    char::REPLACEMENT_CHARACTER
+};
+
+use shift_jis::SHIFT_JIS;
+
+mod shift_jis;
+
+
+// only this much of the start gets searched for a <meta charset>
+const PRESCAN_LENGTH: usize = 1024;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+const UTF8_LABELS: [&str; 6] = [
+    "unicode-1-1-utf-8",
+    "unicode11utf8",
+    "unicode20utf8",
+    "utf-8",
+    "utf8",
+    "x-unicode20utf8"
+];
+
+const UTF16LE_LABELS: [&str; 7] = [
+    "csunicode",
+    "iso-10646-ucs-2",
+    "ucs-2",
+    "unicode",
+    "unicodefeff",
+    "utf-16",
+    "utf-16le"
+];
+
+const UTF16BE_LABELS: [&str; 2] = [
+    "unicodefffe",
+    "utf-16be"
+];
+
+// latin1 and ascii r treated as windows-1252 by every browser
+const WINDOWS1252_LABELS: [&str; 18] = [
+    "ansi_x3.4-1968",
+    "ascii",
+    "cp1252",
+    "cp819",
+    "csisolatin1",
+    "ibm819",
+    "iso-8859-1",
+    "iso-ir-100",
+    "iso8859-1",
+    "iso88591",
+    "iso_8859-1",
+    "iso_8859-1:1987",
+    "l1",
+    "latin1",
+    "us-ascii",
+    "windows-1252",
+    "x-cp1252",
+    "x-user-defined"
+];
+
+const SHIFT_JIS_LABELS: [&str; 8] = [
+    "csshiftjis",
+    "ms932",
+    "ms_kanji",
+    "shift-jis",
+    "shift_jis",
+    "sjis",
+    "windows-31j",
+    "x-sjis"
+];
+
+// 0x80 to 0x9f, the bytes windows-1252 doesnt define stay as c1 controls
+const WINDOWS1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}'
+];
+
+fn is_whitespace(byte: u8) -> bool
+{
+    [b'\t', b'\n', b'\x0C', b'\r', b' '].contains(&byte)
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool
+{
+    bytes.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding
+{
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+    ShiftJis
+}
+
+// why an encoding got picked, from most to least reliable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingSource
+{
+    ByteOrderMark,
+    Hint,
+    Meta,
+    // the encoding in an <?xml ?> declaration
+    Declaration,
+    Guess
+}
+
+impl Encoding
+{
+    pub fn for_label(label: &str) -> Option<Self>
+    {
+        let label = label.trim_matches(|c: char| c.is_ascii_whitespace()).to_ascii_lowercase();
+        let label = label.as_str();
+
+        if UTF8_LABELS.contains(&label)
+        {
+            Some(Self::Utf8)
+        } else if UTF16LE_LABELS.contains(&label)
+        {
+            Some(Self::Utf16Le)
+        } else if UTF16BE_LABELS.contains(&label)
+        {
+            Some(Self::Utf16Be)
+        } else if WINDOWS1252_LABELS.contains(&label)
+        {
+            Some(Self::Windows1252)
+        } else if SHIFT_JIS_LABELS.contains(&label)
+        {
+            Some(Self::ShiftJis)
+        } else
+        {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Windows1252 => "windows-1252",
+            Self::ShiftJis => "Shift_JIS"
+        }
+    }
+
+    // same order as browsers: byte order mark, then whatever the caller knows
+    // (like a content-type header), then a <meta> in the start of the document
+    pub fn detect(bytes: &[u8], hint: Option<Self>) -> (Self, EncodingSource)
+    {
+        if let Some(encoding) = Self::from_bom(bytes)
+        {
+            return (encoding, EncodingSource::ByteOrderMark);
+        }
+
+        if let Some(encoding) = hint
+        {
+            return (encoding, EncodingSource::Hint);
+        }
+
+        if let Some(encoding) = Prescanner::new(bytes).scan()
+        {
+            return (encoding, EncodingSource::Meta);
+        }
+
+        let encoding = if std::str::from_utf8(bytes).is_ok()
+        {
+            Self::Utf8
+        } else
+        {
+            Self::Windows1252
+        };
+
+        (encoding, EncodingSource::Guess)
+    }
+
+    // xml has no <meta>, the declaration at the very start says the encoding
+    // and without one its always utf-8
+    pub fn detect_xml(bytes: &[u8], hint: Option<Self>) -> (Self, EncodingSource)
+    {
+        if let Some(encoding) = Self::from_bom(bytes)
+        {
+            return (encoding, EncodingSource::ByteOrderMark);
+        }
+
+        if let Some(encoding) = hint
+        {
+            return (encoding, EncodingSource::Hint);
+        }
+
+        if let Some(encoding) = Self::from_declaration(bytes)
+        {
+            return (encoding, EncodingSource::Declaration);
+        }
+
+        (Self::Utf8, EncodingSource::Guess)
+    }
+
+    fn from_declaration(bytes: &[u8]) -> Option<Self>
+    {
+        let declaration = bytes.strip_prefix(b"<?xml")?;
+        let end = declaration.windows(2).position(|pair| pair == b"?>")?;
+
+        // everything before the encoding is ascii so this works for any of the ascii compatible ones
+        let declaration = std::str::from_utf8(&declaration[..end]).ok()?;
+
+        let (_, rest) = declaration.split_once("encoding")?;
+        let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let (label, _) = rest[1..].split_once(quote)?;
+
+        Self::for_label(label)
+    }
+
+    fn from_bom(bytes: &[u8]) -> Option<Self>
+    {
+        if bytes.starts_with(UTF8_BOM)
+        {
+            Some(Self::Utf8)
+        } else if bytes.starts_with(UTF16LE_BOM)
+        {
+            Some(Self::Utf16Le)
+        } else if bytes.starts_with(UTF16BE_BOM)
+        {
+            Some(Self::Utf16Be)
+        } else
+        {
+            None
+        }
+    }
+
+    // invalid sequences turn into replacement characters, a matching bom gets dropped
+    pub fn decode(&self, bytes: &[u8]) -> String
+    {
+        match self
+        {
+            Self::Utf8 =>
+            {
+                let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+
+                String::from_utf8_lossy(bytes).into_owned()
+            },
+            Self::Utf16Le =>
+            {
+                let bytes = bytes.strip_prefix(UTF16LE_BOM).unwrap_or(bytes);
+
+                Self::decode_utf16(bytes, u16::from_le_bytes)
+            },
+            Self::Utf16Be =>
+            {
+                let bytes = bytes.strip_prefix(UTF16BE_BOM).unwrap_or(bytes);
+
+                Self::decode_utf16(bytes, u16::from_be_bytes)
+            },
+            Self::Windows1252 =>
+            {
+                bytes.iter().map(|&byte|
+                {
+                    match byte
+                    {
+                        0x80..=0x9F => WINDOWS1252_HIGH[(byte - 0x80) as usize],
+                        _ => byte as char
+                    }
+                }).collect()
+            },
+            Self::ShiftJis => Self::decode_shift_jis(bytes)
+        }
+    }
+
+    fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> String
+    {
+        let pairs = bytes.chunks_exact(2);
+        let is_odd = !pairs.remainder().is_empty();
+
+        let units = pairs.map(|pair| to_unit([pair[0], pair[1]]));
+
+        let mut text = char::decode_utf16(units)
+            .map(|c| c.unwrap_or(REPLACEMENT_CHARACTER))
+            .collect::<String>();
+
+        // half of a code unit at the end
+        if is_odd
+        {
+            text.push(REPLACEMENT_CHARACTER);
+        }
+
+        text
+    }
+
+    fn decode_shift_jis(bytes: &[u8]) -> String
+    {
+        let mut text = String::with_capacity(bytes.len());
+
+        let mut index = 0;
+        while let Some(&byte) = bytes.get(index)
+        {
+            index += 1;
+
+            let lead = match byte
+            {
+                0x00..=0x80 =>
+                {
+                    text.push(byte as char);
+
+                    continue;
+                },
+                // halfwidth katakana
+                0xA1..=0xDF =>
+                {
+                    text.push(char::from_u32(0xFF61 + (byte - 0xA1) as u32).unwrap());
+
+                    continue;
+                },
+                0x81..=0x9F => byte - 0x81,
+                0xE0..=0xFC => byte - 0xC1,
+                _ =>
+                {
+                    text.push(REPLACEMENT_CHARACTER);
+
+                    continue;
+                }
+            };
+
+            let trail = match bytes.get(index)
+            {
+                Some(&trail @ 0x40..=0x7E) => trail - 0x40,
+                Some(&trail @ 0x80..=0xFC) => trail - 0x41,
+                // a broken pair doesnt eat the byte after it
+                _ =>
+                {
+                    text.push(REPLACEMENT_CHARACTER);
+
+                    continue;
+                }
+            };
+
+            let code = SHIFT_JIS[lead as usize * 188 + trail as usize];
+
+            match char::from_u32(code as u32).filter(|_| code != 0)
+            {
+                Some(c) =>
+                {
+                    text.push(c);
+                    index += 1;
+                },
+                None =>
+                {
+                    text.push(REPLACEMENT_CHARACTER);
+
+                    // ascii trail bytes r still their own characters
+                    if !bytes[index].is_ascii()
+                    {
+                        index += 1;
+                    }
+                }
+            }
+        }
+
+        text
+    }
+}
+
+impl fmt::Display for Encoding
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}", self.name())
+    }
+}
+
+// the whatwg prescan, looks through the start of the bytes for a <meta> that says
+// what the encoding is without actually parsing anything
+struct Prescanner<'a>
+{
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl<'a> Prescanner<'a>
+{
+    fn new(bytes: &'a [u8]) -> Self
+    {
+        let bytes = &bytes[..bytes.len().min(PRESCAN_LENGTH)];
+
+        Self{bytes, position: 0}
+    }
+
+    fn rest(&self) -> &'a [u8]
+    {
+        self.bytes.get(self.position..).unwrap_or_default()
+    }
+
+    fn current(&self) -> Option<u8>
+    {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn skip_while(&mut self, predicate: impl Fn(u8) -> bool)
+    {
+        while self.current().is_some_and(&predicate)
+        {
+            self.position += 1;
+        }
+    }
+
+    fn scan(mut self) -> Option<Encoding>
+    {
+        while self.position < self.bytes.len()
+        {
+            let rest = self.rest();
+
+            let is_meta = starts_with_ignore_case(rest, b"<meta")
+                && rest.get(5).is_some_and(|&byte| is_whitespace(byte) || byte == b'/');
+
+            let is_tag = rest.first() == Some(&b'<') && match rest.get(1)
+            {
+                Some(b'/') => rest.get(2).is_some_and(|byte| byte.is_ascii_alphabetic()),
+                Some(byte) => byte.is_ascii_alphabetic(),
+                None => false
+            };
+
+            if rest.starts_with(b"<!--")
+            {
+                // the dashes of <!-- count, so <!--> is a whole comment
+                let end = rest[2..].windows(3).position(|window| window == b"-->");
+
+                self.position += end.map(|end| end + 5).unwrap_or(rest.len());
+
+                continue;
+            } else if is_meta
+            {
+                self.position += 5;
+
+                if let Some(encoding) = self.parse_meta()
+                {
+                    return Some(encoding);
+                }
+
+                continue;
+            } else if is_tag
+            {
+                self.skip_while(|byte| !is_whitespace(byte) && byte != b'>');
+
+                while self.parse_attribute().is_some() {}
+            } else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?")
+            {
+                self.skip_while(|byte| byte != b'>');
+            }
+
+            self.position += 1;
+        }
+
+        None
+    }
+
+    fn parse_meta(&mut self) -> Option<Encoding>
+    {
+        let mut names = Vec::new();
+        let mut got_pragma = false;
+        let mut need_pragma = None;
+        let mut charset = None;
+
+        while let Some((name, value)) = self.parse_attribute()
+        {
+            if names.contains(&name)
+            {
+                continue;
+            }
+
+            match name.as_str()
+            {
+                "http-equiv" => got_pragma |= value == "content-type",
+                "content" if charset.is_none() =>
+                {
+                    if let Some(label) = Self::content_charset(&value)
+                    {
+                        charset = Encoding::for_label(&label);
+                        need_pragma = Some(true);
+                    }
+                },
+                "charset" =>
+                {
+                    charset = Encoding::for_label(&value);
+                    need_pragma = Some(false);
+                },
+                _ => ()
+            }
+
+            names.push(name);
+        }
+
+        match need_pragma
+        {
+            None => return None,
+            Some(true) if !got_pragma => return None,
+            _ => ()
+        }
+
+        // a document that got this far cant really be utf-16
+        match charset?
+        {
+            Encoding::Utf16Le | Encoding::Utf16Be => Some(Encoding::Utf8),
+            encoding => Some(encoding)
+        }
+    }
+
+    // names and values come back lowercased, none means the tag ended
+    fn parse_attribute(&mut self) -> Option<(String, String)>
+    {
+        self.skip_while(|byte| is_whitespace(byte) || byte == b'/');
+
+        let mut name = String::new();
+        loop
+        {
+            match self.current()?
+            {
+                b'>' if name.is_empty() => return None,
+                b'=' if !name.is_empty() =>
+                {
+                    self.position += 1;
+
+                    break;
+                },
+                byte if is_whitespace(byte) =>
+                {
+                    self.skip_while(is_whitespace);
+
+                    if self.current()? != b'='
+                    {
+                        return Some((name, String::new()));
+                    }
+
+                    self.position += 1;
+
+                    break;
+                },
+                b'/' | b'>' => return Some((name, String::new())),
+                byte =>
+                {
+                    name.push(byte.to_ascii_lowercase() as char);
+                    self.position += 1;
+                }
+            }
+        }
+
+        self.skip_while(is_whitespace);
+
+        let mut value = String::new();
+        match self.current()?
+        {
+            quote @ (b'"' | b'\'') =>
+            {
+                self.position += 1;
+
+                loop
+                {
+                    let byte = self.current()?;
+                    self.position += 1;
+
+                    if byte == quote
+                    {
+                        return Some((name, value));
+                    }
+
+                    value.push(byte.to_ascii_lowercase() as char);
+                }
+            },
+            b'>' => return Some((name, value)),
+            _ => ()
+        }
+
+        while let Some(byte) = self.current().filter(|&byte| !is_whitespace(byte) && byte != b'>')
+        {
+            value.push(byte.to_ascii_lowercase() as char);
+            self.position += 1;
+        }
+
+        Some((name, value))
+    }
+
+    // the charset=whatever part of <meta http-equiv="content-type" content="...">
+    fn content_charset(content: &str) -> Option<String>
+    {
+        let mut rest = content;
+
+        loop
+        {
+            let start = rest.find("charset")?;
+            rest = rest[start + "charset".len()..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+
+            if let Some(value) = rest.strip_prefix('=')
+            {
+                rest = value.trim_start_matches(|c: char| c.is_ascii_whitespace());
+
+                break;
+            }
+        }
+
+        match rest.chars().next()?
+        {
+            quote @ ('"' | '\'') =>
+            {
+                let value = &rest[1..];
+
+                value.find(quote).map(|end| value[..end].to_owned())
+            },
+            _ =>
+            {
+                let end = rest.find(|c: char| c.is_ascii_whitespace() || c == ';').unwrap_or(rest.len());
+
+                (end != 0).then(|| rest[..end].to_owned())
+            }
+        }
+    }
+}
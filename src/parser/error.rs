@@ -0,0 +1,506 @@
+use std::{
+    fmt,
+    error::Error
+};
+
+use super::Position;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind
+{
+    UnexpectedLexeme,
+    UnexpectedLeaf,
+    UnparsedText
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError
+{
+    kind: ParseErrorKind,
+    expected: String,
+    found: String,
+    line: Option<u64>
+}
+
+impl ParseError
+{
+    pub fn new(
+        kind: ParseErrorKind,
+        expected: impl Into<String>,
+        found: impl Into<String>,
+        line: Option<u64>
+    ) -> Self
+    {
+        Self{kind, expected: expected.into(), found: found.into(), line}
+    }
+
+    pub fn kind(&self) -> ParseErrorKind
+    {
+        self.kind
+    }
+
+    pub fn expected(&self) -> &str
+    {
+        &self.expected
+    }
+
+    pub fn found(&self) -> &str
+    {
+        &self.found
+    }
+
+    pub fn line(&self) -> Option<u64>
+    {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        if let Some(line) = self.line
+        {
+            write!(f, "line {line}: ")?;
+        }
+
+        match self.kind
+        {
+            ParseErrorKind::UnparsedText =>
+            {
+                write!(f, "unparsed text: {}", self.found)
+            },
+            _ =>
+            {
+                write!(f, "unexpected {}, expected {}", self.found, self.expected)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinifyErrorKind
+{
+    // the text couldnt be parsed in the first place
+    Parse,
+    // minified output that parses into something else than what it was minified from
+    ChangedTree
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinifyError
+{
+    kind: MinifyErrorKind,
+    parse_error: Option<ParseError>
+}
+
+impl MinifyError
+{
+    pub fn new(kind: MinifyErrorKind, parse_error: Option<ParseError>) -> Self
+    {
+        Self{kind, parse_error}
+    }
+
+    pub fn kind(&self) -> MinifyErrorKind
+    {
+        self.kind
+    }
+
+    pub fn parse_error(&self) -> Option<&ParseError>
+    {
+        self.parse_error.as_ref()
+    }
+}
+
+impl From<ParseError> for MinifyError
+{
+    fn from(err: ParseError) -> Self
+    {
+        Self::new(MinifyErrorKind::Parse, Some(err))
+    }
+}
+
+impl fmt::Display for MinifyError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match (self.kind, &self.parse_error)
+        {
+            (MinifyErrorKind::Parse, Some(err)) => write!(f, "{err}"),
+            (MinifyErrorKind::Parse, None) => write!(f, "the text couldnt be parsed"),
+            (MinifyErrorKind::ChangedTree, _) => write!(f, "minifying changed the document")
+        }
+    }
+}
+
+impl Error for MinifyError
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)>
+    {
+        self.parse_error.as_ref().map(|err| err as &(dyn Error + 'static))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationErrorKind
+{
+    // void elements like br and img
+    Childless,
+    OutOfBounds,
+    NotAnElement
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationError
+{
+    kind: MutationErrorKind,
+    // the element that was being changed
+    name: String,
+    index: Option<usize>
+}
+
+impl MutationError
+{
+    pub fn new(kind: MutationErrorKind, name: impl Into<String>, index: Option<usize>) -> Self
+    {
+        Self{kind, name: name.into(), index}
+    }
+
+    pub fn kind(&self) -> MutationErrorKind
+    {
+        self.kind
+    }
+
+    pub fn name(&self) -> &str
+    {
+        &self.name
+    }
+
+    pub fn index(&self) -> Option<usize>
+    {
+        self.index
+    }
+}
+
+impl fmt::Display for MutationError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let name = &self.name;
+        let index = self.index.map(|index| index.to_string()).unwrap_or_else(|| "none".to_owned());
+
+        match self.kind
+        {
+            MutationErrorKind::Childless => write!(f, "{name} cant have children"),
+            MutationErrorKind::OutOfBounds => write!(f, "{name} has no child at {index}"),
+            MutationErrorKind::NotAnElement => write!(f, "child {index} of {name} isnt an element")
+        }
+    }
+}
+
+impl Error for MutationError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorErrorKind
+{
+    UnexpectedEnd,
+    UnexpectedChar,
+    UnknownPseudoClass,
+    InvalidNth,
+    // pseudo elements and such
+    Unsupported
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError
+{
+    kind: SelectorErrorKind,
+    found: String,
+    // in bytes from the start of the selector
+    offset: usize
+}
+
+impl SelectorError
+{
+    pub fn new(kind: SelectorErrorKind, found: impl Into<String>, offset: usize) -> Self
+    {
+        Self{kind, found: found.into(), offset}
+    }
+
+    pub fn kind(&self) -> SelectorErrorKind
+    {
+        self.kind
+    }
+
+    pub fn found(&self) -> &str
+    {
+        &self.found
+    }
+
+    pub fn offset(&self) -> usize
+    {
+        self.offset
+    }
+}
+
+impl fmt::Display for SelectorError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let found = &self.found;
+
+        write!(f, "offset {}: ", self.offset)?;
+
+        match self.kind
+        {
+            SelectorErrorKind::UnexpectedEnd => write!(f, "selector ended unexpectedly"),
+            SelectorErrorKind::UnexpectedChar => write!(f, "unexpected {found}"),
+            SelectorErrorKind::UnknownPseudoClass => write!(f, "unknown pseudo class {found}"),
+            SelectorErrorKind::InvalidNth => write!(f, "invalid nth expression {found}"),
+            SelectorErrorKind::Unsupported => write!(f, "{found} isnt supported")
+        }
+    }
+}
+
+impl Error for SelectorError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XPathErrorKind
+{
+    UnexpectedToken,
+    UnexpectedEnd,
+    UnknownFunction,
+    WrongArguments,
+    // a path or predicate used on something that isnt a node-set
+    NotANodeSet,
+    // variables
+    Unsupported
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XPathError
+{
+    kind: XPathErrorKind,
+    found: String,
+    // in bytes from the start of the expression, evaluation errors dont have one
+    offset: Option<usize>
+}
+
+impl XPathError
+{
+    pub fn new(kind: XPathErrorKind, found: impl Into<String>, offset: Option<usize>) -> Self
+    {
+        Self{kind, found: found.into(), offset}
+    }
+
+    pub fn kind(&self) -> XPathErrorKind
+    {
+        self.kind
+    }
+
+    pub fn found(&self) -> &str
+    {
+        &self.found
+    }
+
+    pub fn offset(&self) -> Option<usize>
+    {
+        self.offset
+    }
+}
+
+impl fmt::Display for XPathError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let found = &self.found;
+
+        if let Some(offset) = self.offset
+        {
+            write!(f, "offset {offset}: ")?;
+        }
+
+        match self.kind
+        {
+            XPathErrorKind::UnexpectedToken => write!(f, "unexpected {found}"),
+            XPathErrorKind::UnexpectedEnd => write!(f, "expression ended unexpectedly"),
+            XPathErrorKind::UnknownFunction => write!(f, "unknown function {found}"),
+            XPathErrorKind::WrongArguments => write!(f, "wrong arguments for {found}"),
+            XPathErrorKind::NotANodeSet => write!(f, "{found} isnt a node-set"),
+            XPathErrorKind::Unsupported => write!(f, "{found} isnt supported")
+        }
+    }
+}
+
+impl Error for XPathError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmlErrorKind
+{
+    // the text ended in the middle of something
+    UnexpectedEnd,
+    UnexpectedChar,
+    InvalidName,
+    // a character thats not allowed anywhere in xml, like most control characters
+    InvalidChar,
+    InvalidDeclaration,
+    // an end tag that doesnt close the element thats open
+    MismatchedEnd,
+    UnclosedElement,
+    DuplicateAttribute,
+    UnknownEntity,
+    UnboundPrefix,
+    // xml and xmlns cant be bound to anything else
+    ReservedPrefix,
+    MissingRoot,
+    // something other than comments and processing instructions after the root element
+    ContentAfterRoot
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlError
+{
+    kind: XmlErrorKind,
+    found: String,
+    position: Position
+}
+
+impl XmlError
+{
+    pub fn new(kind: XmlErrorKind, found: impl Into<String>, position: Position) -> Self
+    {
+        Self{kind, found: found.into(), position}
+    }
+
+    pub fn kind(&self) -> XmlErrorKind
+    {
+        self.kind
+    }
+
+    pub fn found(&self) -> &str
+    {
+        &self.found
+    }
+
+    pub fn position(&self) -> Position
+    {
+        self.position
+    }
+}
+
+impl fmt::Display for XmlError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let found = &self.found;
+
+        write!(f, "line {}, column {}: ", self.position.line, self.position.column)?;
+
+        match self.kind
+        {
+            XmlErrorKind::UnexpectedEnd => write!(f, "text ended inside of {found}"),
+            XmlErrorKind::UnexpectedChar => write!(f, "unexpected {found}"),
+            XmlErrorKind::InvalidName => write!(f, "{found} isnt a valid name"),
+            XmlErrorKind::InvalidChar => write!(f, "{found} isnt allowed in xml"),
+            XmlErrorKind::InvalidDeclaration => write!(f, "invalid xml declaration, {found}"),
+            XmlErrorKind::MismatchedEnd => write!(f, "{found} doesnt close the open element"),
+            XmlErrorKind::UnclosedElement => write!(f, "{found} was never closed"),
+            XmlErrorKind::DuplicateAttribute => write!(f, "duplicate {found} attribute"),
+            XmlErrorKind::UnknownEntity => write!(f, "unknown entity {found}"),
+            XmlErrorKind::UnboundPrefix => write!(f, "{found} prefix was never declared"),
+            XmlErrorKind::ReservedPrefix => write!(f, "{found} cant be declared like that"),
+            XmlErrorKind::MissingRoot => write!(f, "theres no root element"),
+            XmlErrorKind::ContentAfterRoot => write!(f, "{found} after the root element")
+        }
+    }
+}
+
+impl Error for XmlError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind
+{
+    StrayEnd,
+    UnclosedElement,
+    MisnestedElement,
+    DuplicateAttribute,
+    // a /> on an html element that isnt void, it doesnt close anything
+    SelfClosingElement,
+    // a < that doesnt start a tag, or a </> without a name
+    StrayBracket,
+    // something in a tag that isnt an attribute, like a / in the middle or a value with no name
+    StrayToken,
+    // an attribute with an = and nothing after it
+    MissingValue,
+    UnterminatedLiteral,
+    UnknownEntity
+}
+
+// something that was wrong with the input but got recovered from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic
+{
+    kind: DiagnosticKind,
+    name: String,
+    position: Position
+}
+
+impl Diagnostic
+{
+    pub fn new(kind: DiagnosticKind, name: impl Into<String>, position: Position) -> Self
+    {
+        Self{kind, name: name.into(), position}
+    }
+
+    pub fn kind(&self) -> DiagnosticKind
+    {
+        self.kind
+    }
+
+    pub fn name(&self) -> &str
+    {
+        &self.name
+    }
+
+    pub fn line(&self) -> u64
+    {
+        self.position.line
+    }
+
+    pub fn column(&self) -> u64
+    {
+        self.position.column
+    }
+
+    pub fn position(&self) -> Position
+    {
+        self.position
+    }
+}
+
+impl fmt::Display for Diagnostic
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let name = &self.name;
+
+        write!(f, "line {}, column {}: ", self.position.line, self.position.column)?;
+
+        match self.kind
+        {
+            DiagnosticKind::StrayEnd => write!(f, "ignored stray {name} end"),
+            DiagnosticKind::UnclosedElement => write!(f, "{name} was never closed"),
+            DiagnosticKind::MisnestedElement => write!(f, "misnested {name}"),
+            DiagnosticKind::DuplicateAttribute => write!(f, "duplicate {name} attribute"),
+            DiagnosticKind::SelfClosingElement => write!(f, "ignored the / on a {name} start"),
+            DiagnosticKind::StrayBracket => write!(f, "stray {name} isnt a tag"),
+            DiagnosticKind::StrayToken => write!(f, "ignored {name} in a tag"),
+            DiagnosticKind::MissingValue => write!(f, "{name} has an = but no value"),
+            DiagnosticKind::UnterminatedLiteral => write!(f, "literal opened with {name} never closed"),
+            DiagnosticKind::UnknownEntity => write!(f, "unknown entity {name}")
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use std::{
+    mem,
+    borrow::Cow,
+    collections::VecDeque
+};
+
+use super::{
+    Doctype,
+    Encoding,
+    EncodingSource,
+    Position,
+    Span,
+    ParseError,
+    Diagnostic,
+    DiagnosticKind,
+    Namespace,
+    foreign::is_breakout,
+    syntaxer::{
+        Syntaxer,
+        Leaf,
+        ElementBody,
+        ElementEnd,
+        Text
+    },
+    tree_builder::{
+        implied_end,
+        find_in_scope,
+        is_childless,
+        is_formatting,
+        is_marker,
+        has_optional_end,
+        reconstructs,
+        adjust_foreign
+    }
+};
+
+
+// ends that never appeared in the text (implied or of childless elements) have an empty span,
+// so do the starts of formatting elements that got reopened
+#[derive(Debug, Clone)]
+pub enum Event<'a>
+{
+    Start(ElementBody<'a>),
+    End(ElementEnd<'a>),
+    Text(Text<'a>),
+    Comment(&'a str),
+    Doctype(Doctype)
+}
+
+impl<'a> Event<'a>
+{
+    pub fn dispatch(&self, handler: &mut impl Handler)
+    {
+        match self
+        {
+            Self::Start(body) => handler.start(body),
+            Self::End(end) => handler.end(end),
+            Self::Text(text) => handler.text(text),
+            Self::Comment(comment) => handler.comment(comment),
+            Self::Doctype(doctype) => handler.doctype(doctype)
+        }
+    }
+}
+
+pub trait Handler
+{
+    fn start(&mut self, _body: &ElementBody<'_>) {}
+    fn end(&mut self, _end: &ElementEnd<'_>) {}
+    fn text(&mut self, _text: &Text<'_>) {}
+    fn comment(&mut self, _comment: &str) {}
+    fn doctype(&mut self, _doctype: &Doctype) {}
+}
+
+struct OpenElement<'a>
+{
+    name: Cow<'a, str>,
+    position: Position,
+    namespace: Namespace,
+    is_integration_point: bool,
+    // html formatting elements get sent again if they r reopened
+    body: Option<ElementBody<'a>>
+}
+
+impl<'a> AsRef<str> for OpenElement<'a>
+{
+    fn as_ref(&self) -> &str
+    {
+        &self.name
+    }
+}
+
+// a formatting element that something else closed, its start gets sent again before more content
+struct Pending<'a>
+{
+    body: ElementBody<'a>,
+    depth: usize
+}
+
+// the leaves of the syntaxer but balanced, without ever building a tree
+// it makes the same decisions as the tree builder except for one thing, a formatting element
+// thats misnested around a block like <b><p>x</b> gets its inside moved into a copy in the tree,
+// here the start of the block was already sent so the end tag just closes everything up to it
+pub struct Events<'a>
+{
+    syntaxer: Syntaxer<'a>,
+    encoding: Option<(Encoding, EncodingSource)>,
+    open: Vec<OpenElement<'a>>,
+    pending: Vec<Pending<'a>>,
+    queued: VecDeque<Event<'a>>,
+    diagnostics: Vec<Diagnostic>,
+    // where the last leaf ended, implied ends at the end of the text go here
+    position: Position
+}
+
+impl<'a> Events<'a>
+{
+    pub fn new(syntaxer: Syntaxer<'a>, encoding: Option<(Encoding, EncodingSource)>) -> Self
+    {
+        Self{
+            syntaxer,
+            encoding,
+            open: Vec::new(),
+            pending: Vec::new(),
+            queued: VecDeque::new(),
+            diagnostics: Vec::new(),
+            position: Position::default()
+        }
+    }
+
+    pub fn encoding(&self) -> Option<Encoding>
+    {
+        self.encoding.map(|(encoding, _)| encoding)
+    }
+
+    pub fn encoding_source(&self) -> Option<EncodingSource>
+    {
+        self.encoding.map(|(_, source)| source)
+    }
+
+    // everything recovered from so far
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic>
+    {
+        let mut diagnostics = self.syntaxer.take_diagnostics();
+        diagnostics.append(&mut self.diagnostics);
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.position());
+
+        diagnostics
+    }
+
+    pub fn handle(&mut self, handler: &mut impl Handler) -> Result<(), ParseError>
+    {
+        for event in self
+        {
+            event?.dispatch(handler);
+        }
+
+        Ok(())
+    }
+
+    fn diagnose(&mut self, kind: DiagnosticKind, name: &str, position: Position)
+    {
+        self.diagnostics.push(Diagnostic::new(kind, name, position));
+    }
+
+    fn process(&mut self, leaf: Leaf<'a>)
+    {
+        match leaf
+        {
+            Leaf::Body(body) =>
+            {
+                self.position = body.span.end;
+
+                self.start(body);
+            },
+            Leaf::End(end) =>
+            {
+                self.position = end.span.end;
+
+                self.end(end);
+            },
+            Leaf::Content(text) =>
+            {
+                self.position = text.span().end;
+
+                if !text.raw().trim().is_empty()
+                {
+                    self.reconstruct(text.span().start);
+                }
+
+                self.queued.push_back(Event::Text(text));
+            },
+            Leaf::Comment(comment, _) => self.queued.push_back(Event::Comment(comment)),
+            Leaf::Doctype(doctype) => self.queued.push_back(Event::Doctype(doctype))
+        }
+    }
+
+    fn start(&mut self, mut body: ElementBody<'a>)
+    {
+        let namespace = self.namespace_for(&body);
+
+        if namespace.is_foreign()
+        {
+            adjust_foreign(&mut body, namespace);
+
+            // like xml, nothing gets closed implicitly and anything can have children
+            self.open_element(body, namespace);
+
+            return;
+        }
+
+        self.close_to(implied_end(&self.open, &body.name), body.span.start, false, None);
+
+        // links cant be nested so an open one gets closed
+        if body.name == "a"
+        {
+            if let Some(index) = self.open.iter().rposition(|element| element.name == "a")
+            {
+                self.diagnose(DiagnosticKind::MisnestedElement, "a", body.position);
+
+                self.close_to(index, body.span.start, false, None);
+            }
+        }
+
+        if reconstructs(&body.name)
+        {
+            self.reconstruct(body.span.start);
+        }
+
+        if is_childless(&body.name)
+        {
+            let name = body.name.clone();
+
+            self.queued.push_back(Event::Start(body));
+            self.queued.push_back(Event::End(Self::implied(name, self.position)));
+        } else
+        {
+            self.open_element(body, Namespace::Html);
+        }
+    }
+
+    fn end(&mut self, mut end: ElementEnd<'a>)
+    {
+        // the end tag got lowercased too so it needs the same fixing to match
+        let is_svg_open = self.open.iter().any(|element| element.name == "svg");
+        if let Some(adjusted) = Namespace::Svg.adjust_element(&end.name).filter(|_| is_svg_open)
+        {
+            end.name = Cow::Borrowed(adjusted);
+        }
+
+        if let Some(index) = self.foreign_end(&end.name)
+        {
+            self.close_to(index, end.span.start, false, Some(end));
+
+            return;
+        }
+
+        // the end of <br/> and such, those r already closed
+        if is_childless(&end.name) && !self.open.iter().any(|element| element.name == end.name)
+        {
+            return;
+        }
+
+        // only foreign elements close themselves with a />, its ignored on html ones
+        if end.is_self_closing
+        {
+            self.diagnose(DiagnosticKind::SelfClosingElement, &end.name, end.position);
+
+            return;
+        }
+
+        let index = match find_in_scope(&self.open, &end.name)
+        {
+            Some(x) => x,
+            None =>
+            {
+                let is_open = self.open.iter().any(|element| element.name == end.name);
+                let pending = self.pending.iter().rposition(|pending| pending.body.name == end.name);
+
+                match pending.filter(|_| !is_open && is_formatting(&end.name))
+                {
+                    // the element already got closed by something else
+                    Some(pending) =>
+                    {
+                        self.pending.remove(pending);
+                    },
+                    None => self.diagnose(DiagnosticKind::StrayEnd, &end.name, end.position)
+                }
+
+                return;
+            }
+        };
+
+        let is_unclosed = !is_formatting(&end.name);
+        if !is_unclosed && index + 1 != self.open.len()
+        {
+            self.diagnose(DiagnosticKind::MisnestedElement, &end.name, end.position);
+        }
+
+        self.close_to(index, end.span.start, is_unclosed, Some(end));
+    }
+
+    fn open_element(&mut self, body: ElementBody<'a>, namespace: Namespace)
+    {
+        let is_formatting = namespace == Namespace::Html && is_formatting(&body.name);
+
+        let element = OpenElement{
+            name: body.name.clone(),
+            position: body.position,
+            namespace,
+            is_integration_point: namespace.is_integration_point(&body.name, &body.tags),
+            body: is_formatting.then(|| body.clone())
+        };
+
+        self.queued.push_back(Event::Start(body));
+        self.open.push(element);
+    }
+
+    // the same as in the tree builder, html elements that cant be in foreign content close it
+    fn namespace_for(&mut self, body: &ElementBody<'a>) -> Namespace
+    {
+        let name = body.name.as_ref();
+
+        let current = match self.open.last()
+        {
+            Some(current) => current,
+            None => return Namespace::from_root(name).unwrap_or_default()
+        };
+
+        let namespace = current.namespace;
+
+        let is_mathml_text = namespace == Namespace::MathMl && current.name != "annotation-xml";
+
+        let is_html_content = !namespace.is_foreign() || (current.is_integration_point
+            && !(is_mathml_text && matches!(name, "mglyph" | "malignmark")));
+
+        if is_html_content
+        {
+            return Namespace::from_root(name).unwrap_or_default();
+        }
+
+        if namespace == Namespace::MathMl && current.name == "annotation-xml" && name == "svg"
+        {
+            return Namespace::Svg;
+        }
+
+        if is_breakout(name, &body.tags)
+        {
+            self.diagnose(DiagnosticKind::MisnestedElement, name, body.position);
+
+            let index = self.open.iter()
+                .rposition(|element| !element.namespace.is_foreign() || element.is_integration_point)
+                .map(|index| index + 1)
+                .unwrap_or(0);
+
+            self.close_to(index, body.span.start, false, None);
+
+            return Namespace::Html;
+        }
+
+        namespace
+    }
+
+    // end tags in foreign content close the closest element with the same name, as long as its foreign
+    fn foreign_end(&self, name: &str) -> Option<usize>
+    {
+        for (index, element) in self.open.iter().enumerate().rev()
+        {
+            if !element.namespace.is_foreign()
+            {
+                return None;
+            }
+
+            if element.name.eq_ignore_ascii_case(name)
+            {
+                return Some(index);
+            }
+        }
+
+        None
+    }
+
+    fn reconstruct(&mut self, position: Position)
+    {
+        let marker = self.open.iter().rposition(|element| is_marker(&element.name));
+
+        // stuff from outside of a marker doesnt get reopened inside of it
+        let (ready, blocked): (Vec<_>, Vec<_>) = mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|pending| marker.map(|marker| marker < pending.depth).unwrap_or(true));
+
+        self.pending = blocked;
+
+        for pending in ready
+        {
+            let mut body = pending.body;
+            body.span = Span::new(position, position);
+
+            self.open_element(body, Namespace::Html);
+        }
+    }
+
+    // closes the open elements from index up, the one at index gets the end if theres one,
+    // is_unclosed reports the ones above it that needed an end tag
+    fn close_to(&mut self, index: usize, position: Position, is_unclosed: bool, end: Option<ElementEnd<'a>>)
+    {
+        let marker = self.open[index.min(self.open.len())..].iter()
+            .position(|element| is_marker(&element.name))
+            .map(|marker| marker + index);
+
+        if let Some(marker) = marker
+        {
+            self.pending.retain(|pending| pending.depth <= marker);
+        }
+
+        let reopened = (index + 1..marker.unwrap_or(self.open.len())).filter_map(|depth|
+        {
+            self.open[depth].body.clone().map(|body| Pending{body, depth})
+        }).collect::<Vec<_>>();
+
+        self.pending.extend(reopened);
+
+        let mut end = end;
+        while self.open.len() > index
+        {
+            let element = self.open.pop().unwrap();
+
+            if self.open.len() == index
+            {
+                if let Some(end) = end.take()
+                {
+                    self.queued.push_back(Event::End(end));
+
+                    continue;
+                }
+            }
+
+            if is_unclosed && !has_optional_end(&element.name)
+            {
+                self.diagnose(DiagnosticKind::UnclosedElement, &element.name, element.position);
+            }
+
+            self.queued.push_back(Event::End(Self::implied(element.name, position)));
+        }
+    }
+
+    fn implied(name: Cow<'a, str>, position: Position) -> ElementEnd<'a>
+    {
+        ElementEnd::new(name, position, Span::new(position, position))
+    }
+}
+
+impl<'a> Iterator for Events<'a>
+{
+    type Item = Result<Event<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop
+        {
+            if let Some(event) = self.queued.pop_front()
+            {
+                return Some(Ok(event));
+            }
+
+            match self.syntaxer.next()
+            {
+                Some(Ok(leaf)) => self.process(leaf),
+                Some(Err(err)) => return Some(Err(err)),
+                None if self.open.is_empty() => return None,
+                None => self.close_to(0, self.position, true, None)
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+// svg and mathml inside of html, names get lowercased like everything else
+// but some of them r supposed to be in camel case so they get fixed back up
+
+use super::Tag;
+
+
+// lowercased to how svg spells them
+const SVG_ELEMENTS: [(&str, &str); 37] = [
+    ("altglyph", "altGlyph"),
+    ("altglyphdef", "altGlyphDef"),
+    ("altglyphitem", "altGlyphItem"),
+    ("animatecolor", "animateColor"),
+    ("animatemotion", "animateMotion"),
+    ("animatetransform", "animateTransform"),
+    ("clippath", "clipPath"),
+    ("feblend", "feBlend"),
+    ("fecolormatrix", "feColorMatrix"),
+    ("fecomponenttransfer", "feComponentTransfer"),
+    ("fecomposite", "feComposite"),
+    ("feconvolvematrix", "feConvolveMatrix"),
+    ("fediffuselighting", "feDiffuseLighting"),
+    ("fedisplacementmap", "feDisplacementMap"),
+    ("fedistantlight", "feDistantLight"),
+    ("fedropshadow", "feDropShadow"),
+    ("feflood", "feFlood"),
+    ("fefunca", "feFuncA"),
+    ("fefuncb", "feFuncB"),
+    ("fefuncg", "feFuncG"),
+    ("fefuncr", "feFuncR"),
+    ("fegaussianblur", "feGaussianBlur"),
+    ("feimage", "feImage"),
+    ("femerge", "feMerge"),
+    ("femergenode", "feMergeNode"),
+    ("femorphology", "feMorphology"),
+    ("feoffset", "feOffset"),
+    ("fepointlight", "fePointLight"),
+    ("fespecularlighting", "feSpecularLighting"),
+    ("fespotlight", "feSpotLight"),
+    ("fetile", "feTile"),
+    ("feturbulence", "feTurbulence"),
+    ("foreignobject", "foreignObject"),
+    ("glyphref", "glyphRef"),
+    ("lineargradient", "linearGradient"),
+    ("radialgradient", "radialGradient"),
+    ("textpath", "textPath")
+];
+
+const SVG_ATTRIBUTES: [(&str, &str); 58] = [
+    ("attributename", "attributeName"),
+    ("attributetype", "attributeType"),
+    ("basefrequency", "baseFrequency"),
+    ("baseprofile", "baseProfile"),
+    ("calcmode", "calcMode"),
+    ("clippathunits", "clipPathUnits"),
+    ("diffuseconstant", "diffuseConstant"),
+    ("edgemode", "edgeMode"),
+    ("filterunits", "filterUnits"),
+    ("glyphref", "glyphRef"),
+    ("gradienttransform", "gradientTransform"),
+    ("gradientunits", "gradientUnits"),
+    ("kernelmatrix", "kernelMatrix"),
+    ("kernelunitlength", "kernelUnitLength"),
+    ("keypoints", "keyPoints"),
+    ("keysplines", "keySplines"),
+    ("keytimes", "keyTimes"),
+    ("lengthadjust", "lengthAdjust"),
+    ("limitingconeangle", "limitingConeAngle"),
+    ("markerheight", "markerHeight"),
+    ("markerunits", "markerUnits"),
+    ("markerwidth", "markerWidth"),
+    ("maskcontentunits", "maskContentUnits"),
+    ("maskunits", "maskUnits"),
+    ("numoctaves", "numOctaves"),
+    ("pathlength", "pathLength"),
+    ("patterncontentunits", "patternContentUnits"),
+    ("patterntransform", "patternTransform"),
+    ("patternunits", "patternUnits"),
+    ("pointsatx", "pointsAtX"),
+    ("pointsaty", "pointsAtY"),
+    ("pointsatz", "pointsAtZ"),
+    ("preservealpha", "preserveAlpha"),
+    ("preserveaspectratio", "preserveAspectRatio"),
+    ("primitiveunits", "primitiveUnits"),
+    ("refx", "refX"),
+    ("refy", "refY"),
+    ("repeatcount", "repeatCount"),
+    ("repeatdur", "repeatDur"),
+    ("requiredextensions", "requiredExtensions"),
+    ("requiredfeatures", "requiredFeatures"),
+    ("specularconstant", "specularConstant"),
+    ("specularexponent", "specularExponent"),
+    ("spreadmethod", "spreadMethod"),
+    ("startoffset", "startOffset"),
+    ("stddeviation", "stdDeviation"),
+    ("stitchtiles", "stitchTiles"),
+    ("surfacescale", "surfaceScale"),
+    ("systemlanguage", "systemLanguage"),
+    ("tablevalues", "tableValues"),
+    ("targetx", "targetX"),
+    ("targety", "targetY"),
+    ("textlength", "textLength"),
+    ("viewbox", "viewBox"),
+    ("viewtarget", "viewTarget"),
+    ("xchannelselector", "xChannelSelector"),
+    ("ychannelselector", "yChannelSelector"),
+    ("zoomandpan", "zoomAndPan")
+];
+
+const MATHML_ATTRIBUTES: [(&str, &str); 1] = [
+    ("definitionurl", "definitionURL")
+];
+
+// elements whose children r html again
+const SVG_INTEGRATION_POINTS: [&str; 3] = ["foreignObject", "desc", "title"];
+const MATHML_INTEGRATION_POINTS: [&str; 5] = ["mi", "mo", "mn", "ms", "mtext"];
+
+// annotation-xml only has html in it when its encoding is one of these
+const HTML_ENCODINGS: [&str; 2] = ["text/html", "application/xhtml+xml"];
+
+// html start tags that cant be inside of foreign content, they close it instead
+const BREAKOUT_ELEMENTS: [&str; 44] = [
+    "b",
+    "big",
+    "blockquote",
+    "body",
+    "br",
+    "center",
+    "code",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "listing",
+    "menu",
+    "meta",
+    "nobr",
+    "ol",
+    "p",
+    "pre",
+    "ruby",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "strike",
+    "sub",
+    "sup",
+    "table",
+    "tt",
+    "u",
+    "ul",
+    "var"
+];
+
+// a font start tag only breaks out if it has one of these
+const FONT_BREAKOUT_TAGS: [&str; 3] = ["color", "face", "size"];
+
+pub(super) fn is_breakout(name: &str, tags: &[Tag<'_>]) -> bool
+{
+    BREAKOUT_ELEMENTS.contains(&name)
+        || (name == "font" && tags.iter().any(|tag| FONT_BREAKOUT_TAGS.contains(&tag.name())))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Namespace
+{
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+    // the rest r only for attributes, like xlink:href or xml:lang
+    XLink,
+    Xml,
+    Xmlns,
+    // any other xml namespace or none at all, Element::namespace_uri has the actual uri
+    Other
+}
+
+impl Namespace
+{
+    pub fn uri(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Html => "http://www.w3.org/1999/xhtml",
+            Self::Svg => "http://www.w3.org/2000/svg",
+            Self::MathMl => "http://www.w3.org/1998/Math/MathML",
+            Self::XLink => "http://www.w3.org/1999/xlink",
+            Self::Xml => "http://www.w3.org/XML/1998/namespace",
+            Self::Xmlns => "http://www.w3.org/2000/xmlns/",
+            Self::Other => ""
+        }
+    }
+
+    pub fn from_uri(uri: &str) -> Option<Self>
+    {
+        [Self::Html, Self::Svg, Self::MathMl, Self::XLink, Self::Xml, Self::Xmlns]
+            .into_iter()
+            .find(|namespace| namespace.uri() == uri)
+    }
+
+    // the namespace an attribute prefix stands for in html, nothing else gets resolved
+    pub fn from_prefix(prefix: &str) -> Option<Self>
+    {
+        match prefix
+        {
+            "xlink" => Some(Self::XLink),
+            "xml" => Some(Self::Xml),
+            "xmlns" => Some(Self::Xmlns),
+            _ => None
+        }
+    }
+
+    // the foreign content an element with this name starts
+    pub(super) fn from_root(name: &str) -> Option<Self>
+    {
+        match name
+        {
+            "svg" => Some(Self::Svg),
+            "math" => Some(Self::MathMl),
+            _ => None
+        }
+    }
+
+    pub fn is_foreign(&self) -> bool
+    {
+        *self != Self::Html
+    }
+
+    fn lookup(table: &[(&str, &'static str)], name: &str) -> Option<&'static str>
+    {
+        table.iter().find(|(lowercase, _)| *lowercase == name).map(|(_, adjusted)| *adjusted)
+    }
+
+    pub(super) fn adjust_element(&self, name: &str) -> Option<&'static str>
+    {
+        match self
+        {
+            Self::Svg => Self::lookup(&SVG_ELEMENTS, name),
+            _ => None
+        }
+    }
+
+    pub(super) fn adjust_attribute(&self, name: &str) -> Option<&'static str>
+    {
+        match self
+        {
+            Self::Svg => Self::lookup(&SVG_ATTRIBUTES, name),
+            Self::MathMl => Self::lookup(&MATHML_ATTRIBUTES, name),
+            _ => None
+        }
+    }
+
+    // whether the children of an element with this name and these attributes r html again
+    pub(super) fn is_integration_point(&self, name: &str, tags: &[Tag<'_>]) -> bool
+    {
+        let is_html_encoding = ||
+        {
+            tags.iter().any(|tag|
+            {
+                tag.name() == "encoding" && tag.content().is_some_and(|encoding|
+                {
+                    HTML_ENCODINGS.iter().any(|html| html.eq_ignore_ascii_case(encoding))
+                })
+            })
+        };
+
+        match self
+        {
+            Self::Svg => SVG_INTEGRATION_POINTS.contains(&name),
+            Self::MathMl => MATHML_INTEGRATION_POINTS.contains(&name)
+                || (name == "annotation-xml" && is_html_encoding()),
+            _ => false
+        }
+    }
+}
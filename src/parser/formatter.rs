@@ -0,0 +1,363 @@
+use std::fmt::Write;
+
+use super::{
+    Parser,
+    Child,
+    Element,
+    Document,
+    Tag,
+    Quote,
+    ParseError,
+    Serializer
+};
+
+
+// whitespace inside of these matters so theyre copied exactly
+const VERBATIM_ELEMENTS: [&str; 6] = [
+    "pre",
+    "textarea",
+    "listing",
+    "plaintext",
+    "script",
+    "style"
+];
+
+// elements that flow with text, putting them on their own lines would add spaces
+const INLINE_ELEMENTS: [&str; 33] = [
+    "a",
+    "abbr",
+    "b",
+    "bdi",
+    "bdo",
+    "br",
+    "button",
+    "cite",
+    "code",
+    "data",
+    "dfn",
+    "em",
+    "i",
+    "img",
+    "input",
+    "kbd",
+    "label",
+    "mark",
+    "q",
+    "s",
+    "samp",
+    "select",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "time",
+    "u",
+    "var",
+    "wbr",
+    "font",
+    "nobr"
+];
+
+// characters that make an attribute value need quotes
+const UNQUOTED_FORBIDDEN: [char; 6] = ['"', '\'', '=', '<', '>', '`'];
+
+pub(super) fn is_verbatim(name: &str) -> bool
+{
+    VERBATIM_ELEMENTS.contains(&name)
+}
+
+pub(super) fn is_inline_element(name: &str) -> bool
+{
+    INLINE_ELEMENTS.contains(&name)
+}
+
+pub(super) fn can_unquote(value: &str) -> bool
+{
+    !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || UNQUOTED_FORBIDDEN.contains(&c))
+}
+
+// whether the text of an element ends with its own end tag
+fn has_end(original: &str, name: &str) -> bool
+{
+    let end = match original.rfind("</")
+    {
+        Some(x) => &original[x + 2..],
+        None => return false
+    };
+
+    end.get(..name.len()).is_some_and(|end_name| end_name.eq_ignore_ascii_case(name))
+        && end[name.len()..].trim_start() == ">"
+}
+
+fn is_inline(child: &Child<'_>) -> bool
+{
+    match child
+    {
+        Child::Element(element) => is_inline_element(element.name()),
+        // the whitespace between blocks is just indentation
+        Child::Text(text) => !text.raw().trim().is_empty(),
+        Child::Comment(_) | Child::ProcessingInstruction(_) => false
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Formatter
+{
+    indent: usize,
+    width: usize,
+    quote: Quote,
+    sort_tags: bool
+}
+
+impl Default for Formatter
+{
+    fn default() -> Self
+    {
+        Self{indent: 4, width: 100, quote: Quote::Double, sort_tags: false}
+    }
+}
+
+impl Formatter
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    // spaces per nesting level
+    pub fn indent(mut self, indent: usize) -> Self
+    {
+        self.indent = indent;
+
+        self
+    }
+
+    // start tags longer than this get one attribute per line
+    pub fn width(mut self, width: usize) -> Self
+    {
+        self.width = width;
+
+        self
+    }
+
+    // unquoted values fall back to double quotes when they cant be unquoted
+    pub fn quote(mut self, quote: Quote) -> Self
+    {
+        self.quote = quote;
+
+        self
+    }
+
+    pub fn sort_tags(mut self, sort: bool) -> Self
+    {
+        self.sort_tags = sort;
+
+        self
+    }
+
+    pub fn format(&self, source: &str) -> Result<String, ParseError>
+    {
+        let document = Parser::new(source.chars()).parse()?;
+
+        let formatted = self.format_document(&document, source);
+
+        // formatting something thats already formatted shouldnt change it
+        debug_assert!(
+            Parser::new(formatted.chars()).parse()
+                .map(|document| self.format_document(&document, &formatted) == formatted)
+                .unwrap_or(false),
+            "formatting twice changed the output"
+        );
+
+        Ok(formatted)
+    }
+
+    pub fn is_formatted(&self, source: &str) -> Result<bool, ParseError>
+    {
+        Ok(self.format(source)? == source)
+    }
+
+    // the source is what the document was parsed from, inline content gets copied from it
+    pub fn format_document(&self, document: &Document<'_>, source: &str) -> String
+    {
+        let mut out = String::new();
+
+        if let Some(doctype) = document.doctype()
+        {
+            Serializer::write_doctype(&mut out, doctype).unwrap();
+            out.push('\n');
+        }
+
+        for child in document.children()
+        {
+            self.format_child(&mut out, child, 0, source);
+        }
+
+        out
+    }
+
+    pub fn format_element(&self, element: &Element<'_>, source: &str) -> String
+    {
+        let mut out = String::new();
+        self.write_element(&mut out, element, 0, source);
+
+        out
+    }
+
+    fn write_indent(&self, out: &mut String, depth: usize)
+    {
+        out.extend((0..depth * self.indent).map(|_| ' '));
+    }
+
+    fn format_child(&self, out: &mut String, child: &Child<'_>, depth: usize, source: &str)
+    {
+        match child
+        {
+            Child::Element(element) => self.write_element(out, element, depth, source),
+            Child::Text(text) =>
+            {
+                let text = text.raw().trim();
+
+                if !text.is_empty()
+                {
+                    self.write_indent(out, depth);
+                    out.push_str(text);
+                    out.push('\n');
+                }
+            },
+            Child::Comment(comment) =>
+            {
+                self.write_indent(out, depth);
+                write!(out, "{comment}").unwrap();
+                out.push('\n');
+            },
+            Child::ProcessingInstruction(instruction) =>
+            {
+                self.write_indent(out, depth);
+                writeln!(out, "{instruction}").unwrap();
+            }
+        }
+    }
+
+    fn write_element(&self, out: &mut String, element: &Element<'_>, depth: usize, source: &str)
+    {
+        let name = element.name();
+
+        self.write_indent(out, depth);
+
+        if is_verbatim(name)
+        {
+            if let Some(original) = element.span().slice(source).filter(|_| !element.span().is_empty())
+            {
+                out.push_str(original);
+
+                // an end that was implied by something else isnt in the span
+                if !has_end(original, name)
+                {
+                    write!(out, "</{name}>").unwrap();
+                }
+
+                out.push('\n');
+
+                return;
+            }
+        }
+
+        self.write_start(out, element, depth);
+
+        if element.is_childless()
+        {
+            out.push('\n');
+
+            return;
+        }
+
+        let children = element.children();
+        if children.iter().any(is_inline)
+        {
+            self.write_inline(out, children, source);
+        } else if !children.is_empty()
+        {
+            out.push('\n');
+
+            for child in children
+            {
+                self.format_child(out, child, depth + 1, source);
+            }
+
+            self.write_indent(out, depth);
+        }
+
+        writeln!(out, "</{name}>").unwrap();
+    }
+
+    // everything on one line as it was written, including the whitespace between children
+    fn write_inline(&self, out: &mut String, children: &[Child<'_>], source: &str)
+    {
+        let serializer = Serializer::new().preserve(source);
+
+        let mut inline = String::new();
+        for child in children
+        {
+            serializer.write(&mut inline, child).unwrap();
+        }
+
+        // the element is a block so whitespace at its edges doesnt show up anyway
+        out.push_str(inline.trim_matches(|c: char| c.is_ascii_whitespace()));
+    }
+
+    fn write_start(&self, out: &mut String, element: &Element<'_>, depth: usize)
+    {
+        let mut tags = element.tags().iter().collect::<Vec<_>>();
+
+        if self.sort_tags
+        {
+            tags.sort_by(|a, b| a.name().cmp(b.name()));
+        }
+
+        let tags = tags.into_iter().map(|tag| self.tag(tag)).collect::<Vec<_>>();
+
+        let name = element.name();
+        let length = depth * self.indent
+            + name.len() + 2
+            + tags.iter().map(|tag| tag.len() + 1).sum::<usize>();
+
+        if length <= self.width || tags.len() < 2
+        {
+            write!(out, "<{name}").unwrap();
+            tags.iter().for_each(|tag| write!(out, " {tag}").unwrap());
+        } else
+        {
+            write!(out, "<{name}").unwrap();
+
+            for tag in tags
+            {
+                out.push('\n');
+                self.write_indent(out, depth + 1);
+                out.push_str(&tag);
+            }
+        }
+
+        out.push('>');
+    }
+
+    // values stay as they were written, only the quotes change
+    fn tag(&self, tag: &Tag<'_>) -> String
+    {
+        let name = tag.name();
+
+        let value = match tag.raw_content()
+        {
+            Some(x) => x,
+            None => return name.to_owned()
+        };
+
+        match self.quote
+        {
+            Quote::Unquoted if can_unquote(value) => format!("{name}={value}"),
+            Quote::Single => format!("{name}='{}'", value.replace('\'', "&#39;")),
+            Quote::Double | Quote::Unquoted => format!("{name}=\"{}\"", value.replace('"', "&quot;"))
+        }
+    }
+}
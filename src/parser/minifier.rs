@@ -0,0 +1,498 @@
+use std::{
+    mem,
+    fmt::Write
+};
+
+use super::{
+    Parser,
+    Child,
+    Element,
+    Document,
+    Tag,
+    MinifyError,
+    MinifyErrorKind,
+    Serializer,
+    attributes::is_boolean_attribute,
+    formatter::{
+        is_verbatim,
+        is_inline_element,
+        can_unquote
+    },
+    tree_builder::closes_paragraph
+};
+
+
+// an p end tag cant be left out if its the last thing in one of these
+const P_END_KEEPERS: [&str; 7] = [
+    "a",
+    "audio",
+    "del",
+    "ins",
+    "map",
+    "noscript",
+    "video"
+];
+
+fn is_boolean(tag: &Tag<'_>) -> bool
+{
+    is_boolean_attribute(tag.name())
+}
+
+fn collapse_whitespace(out: &mut String, text: &str)
+{
+    // text right after other text (with a dropped comment in between) continues its whitespace
+    let mut is_space = out.ends_with(' ');
+    for c in text.chars()
+    {
+        if c.is_ascii_whitespace()
+        {
+            if !is_space
+            {
+                out.push(' ');
+            }
+
+            is_space = true;
+        } else
+        {
+            out.push(c);
+
+            is_space = false;
+        }
+    }
+}
+
+// whitespace only text shows up as a space only if theres inline stuff on both sides of it,
+// comments dont count and the edges of an inline element r inline too
+fn is_dropped_whitespace(children: &[Child<'_>], index: usize, is_block: bool) -> bool
+{
+    let is_inline = |child: Option<&Child<'_>>|
+    {
+        match child
+        {
+            Some(Child::Element(element)) => is_inline_element(element.name()),
+            Some(Child::Text(_)) => true,
+            Some(_) => false,
+            None => !is_block
+        }
+    };
+
+    let is_content = |child: &&Child<'_>| !matches!(child, Child::Comment(_));
+
+    let previous = children[..index].iter().rev().find(is_content);
+    let next = children[index + 1..].iter().find(is_content);
+
+    !is_inline(previous) || !is_inline(next)
+}
+
+// whether the end tag of an element can go when next is what comes right after it
+fn can_omit_end(name: &str, parent: Option<&str>, next: Option<&Child<'_>>) -> bool
+{
+    let next = match next
+    {
+        Some(Child::Element(element)) => Some(element.name()),
+        Some(_) => return false,
+        None => None
+    };
+
+    let is_next = |names: &[&str]| next.map(|next| names.contains(&next)).unwrap_or(true);
+
+    match name
+    {
+        "li" => is_next(&["li"]),
+        "dt" | "dd" => is_next(&["dt", "dd"]),
+        "option" => is_next(&["option", "optgroup"]),
+        "optgroup" => is_next(&["optgroup"]),
+        "tr" => is_next(&["tr"]),
+        "td" | "th" => is_next(&["td", "th"]),
+        "thead" => next.is_some() && is_next(&["tbody", "tfoot"]),
+        "tbody" => is_next(&["tbody", "tfoot"]),
+        "tfoot" => next.is_none(),
+        "rb" | "rt" | "rp" => is_next(&["rb", "rt", "rp", "rtc"]),
+        "p" => match next
+        {
+            Some(next) => closes_paragraph(next),
+            None => !parent.is_some_and(|parent| P_END_KEEPERS.contains(&parent))
+        },
+        "head" => is_next(&["body"]),
+        "html" | "body" => next.is_none(),
+        _ => false
+    }
+}
+
+enum Normalized<'b, 'a>
+{
+    Text(String),
+    Child(&'b Child<'a>)
+}
+
+// children the way they show up, text next to each other is joined with its whitespace collapsed
+// and the whitespace that doesnt show up is gone, text in verbatim elements stays like it is
+fn normalize<'b, 'a>(
+    children: &'b [Child<'a>],
+    parent: Option<&Element<'_>>,
+    is_comments: bool
+) -> Vec<Normalized<'b, 'a>>
+{
+    let is_block = parent.map(|parent| !is_inline_element(parent.name())).unwrap_or(true);
+    let is_verbatim = parent.is_some_and(|parent| is_verbatim(parent.name()));
+
+    let mut normalized = Vec::new();
+    let mut text = String::new();
+
+    for (index, child) in children.iter().enumerate()
+    {
+        match child
+        {
+            Child::Text(x) if is_verbatim => text.push_str(x.content()),
+            Child::Text(x) =>
+            {
+                let is_whitespace = x.raw().trim_matches(|c: char| c.is_ascii_whitespace()).is_empty();
+                if !(is_whitespace && is_dropped_whitespace(children, index, is_block))
+                {
+                    collapse_whitespace(&mut text, x.content());
+                }
+            },
+            Child::Comment(_) if !is_comments => (),
+            _ =>
+            {
+                if !text.is_empty()
+                {
+                    normalized.push(Normalized::Text(mem::take(&mut text)));
+                }
+
+                normalized.push(Normalized::Child(child));
+            }
+        }
+    }
+
+    if !text.is_empty()
+    {
+        normalized.push(Normalized::Text(text));
+    }
+
+    // whitespace at the edges of blocks doesnt show up
+    if is_block && !is_verbatim
+    {
+        if let Some(Normalized::Text(text)) = normalized.first_mut()
+        {
+            *text = text.trim_start_matches(' ').to_owned();
+        }
+
+        if let Some(Normalized::Text(text)) = normalized.last_mut()
+        {
+            text.truncate(text.trim_end_matches(' ').len());
+        }
+
+        normalized.retain(|child| !matches!(child, Normalized::Text(text) if text.is_empty()));
+    }
+
+    normalized
+}
+
+// the same tree except for whitespace that doesnt show up, comments if they got dropped and boolean values
+fn is_equivalent(
+    a: &[Child<'_>],
+    b: &[Child<'_>],
+    parent: Option<&Element<'_>>,
+    is_comments: bool
+) -> bool
+{
+    let a = normalize(a, parent, is_comments);
+    let b = normalize(b, parent, is_comments);
+
+    a.len() == b.len() && a.iter().zip(&b).all(|(a, b)|
+    {
+        match (a, b)
+        {
+            (Normalized::Text(a), Normalized::Text(b)) => a == b,
+            (Normalized::Child(Child::Element(a)), Normalized::Child(Child::Element(b))) =>
+            {
+                is_equivalent_element(a, b, is_comments)
+            },
+            (Normalized::Child(Child::Comment(a)), Normalized::Child(Child::Comment(b))) => a.text() == b.text(),
+            (
+                Normalized::Child(Child::ProcessingInstruction(a)),
+                Normalized::Child(Child::ProcessingInstruction(b))
+            ) => a.target() == b.target() && a.data() == b.data(),
+            _ => false
+        }
+    })
+}
+
+fn is_equivalent_element(a: &Element<'_>, b: &Element<'_>, is_comments: bool) -> bool
+{
+    fn value<'b>(tag: &'b Tag<'_>) -> Option<&'b str>
+    {
+        if is_boolean(tag)
+        {
+            None
+        } else
+        {
+            tag.content().filter(|content| !content.is_empty())
+        }
+    }
+
+    let is_same_tags = a.tags().len() == b.tags().len()
+        && a.tags().iter().zip(b.tags()).all(|(a, b)| a.name() == b.name() && value(a) == value(b));
+
+    a.name() == b.name()
+        && is_same_tags
+        && is_equivalent(a.children(), b.children(), Some(a), is_comments)
+}
+
+#[derive(Debug, Clone)]
+pub struct Minifier
+{
+    keep_comments: bool,
+    omit_ends: bool
+}
+
+impl Default for Minifier
+{
+    fn default() -> Self
+    {
+        Self{keep_comments: false, omit_ends: true}
+    }
+}
+
+impl Minifier
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn keep_comments(mut self, keep: bool) -> Self
+    {
+        self.keep_comments = keep;
+
+        self
+    }
+
+    // leaves out end tags that the parser implies anyway
+    pub fn omit_ends(mut self, omit: bool) -> Self
+    {
+        self.omit_ends = omit;
+
+        self
+    }
+
+    // the output gets parsed again and if the tree changed end tags r kept,
+    // if even that doesnt work theres an error instead of output that breaks the page
+    pub fn minify(&self, source: &str) -> Result<String, MinifyError>
+    {
+        let document = Parser::new(source.chars()).parse()?;
+
+        let attempts: &[bool] = if self.omit_ends { &[true, false] } else { &[false] };
+        for &omit_ends in attempts
+        {
+            let minifier = Self{omit_ends, ..self.clone()};
+            let minified = minifier.minify_document(&document);
+
+            if self.is_equivalent(&document, &minified)
+            {
+                return Ok(minified);
+            }
+        }
+
+        Err(MinifyError::new(MinifyErrorKind::ChangedTree, None))
+    }
+
+    pub fn minify_document(&self, document: &Document<'_>) -> String
+    {
+        let mut out = String::new();
+
+        if let Some(doctype) = document.doctype()
+        {
+            Serializer::write_doctype(&mut out, doctype).unwrap();
+        }
+
+        self.write_children(&mut out, document.children(), None);
+
+        out
+    }
+
+    fn is_equivalent(&self, document: &Document<'_>, minified: &str) -> bool
+    {
+        let reparsed = match Parser::new(minified.chars()).parse()
+        {
+            Ok(x) => x,
+            Err(_) => return false
+        };
+
+        is_equivalent(document.children(), reparsed.children(), None, self.keep_comments)
+    }
+
+    fn write_children(
+        &self,
+        out: &mut String,
+        children: &[Child<'_>],
+        parent: Option<&Element<'_>>
+    )
+    {
+        let parent_name = parent.map(|parent| parent.name());
+        let is_block = parent_name.map(|name| !is_inline_element(name)).unwrap_or(true);
+
+        let start = out.len();
+
+        for (index, child) in children.iter().enumerate()
+        {
+            match child
+            {
+                Child::Element(element) =>
+                {
+                    // whatever comes next in the output, not the stuff that gets dropped
+                    let next = children[index + 1..].iter().find(|child|
+                    {
+                        match child
+                        {
+                            Child::Element(_) => true,
+                            Child::Text(text) => !text.raw().trim().is_empty(),
+                            Child::Comment(_) => self.keep_comments,
+                            Child::ProcessingInstruction(_) => true
+                        }
+                    });
+
+                    self.write_element(out, element, parent_name, next);
+                },
+                Child::Text(text) =>
+                {
+                    let is_whitespace = text.raw().trim_matches(|c: char| c.is_ascii_whitespace()).is_empty();
+                    if is_whitespace && is_dropped_whitespace(children, index, is_block)
+                    {
+                        continue;
+                    }
+
+                    collapse_whitespace(out, text.raw());
+                },
+                Child::Comment(comment) =>
+                {
+                    if !self.keep_comments
+                    {
+                        continue;
+                    }
+
+                    write!(out, "{comment}").unwrap();
+                },
+                Child::ProcessingInstruction(instruction) => write!(out, "{instruction}").unwrap()
+            }
+        }
+
+        // whitespace at the edges of blocks doesnt show up
+        if is_block
+        {
+            let written = &out[start..];
+
+            let end = start + written.trim_end_matches(' ').len();
+            out.truncate(end);
+
+            let leading = out[start..].len() - out[start..].trim_start_matches(' ').len();
+            out.replace_range(start..start + leading, "");
+        }
+    }
+
+    fn write_element(
+        &self,
+        out: &mut String,
+        element: &Element<'_>,
+        parent: Option<&str>,
+        next: Option<&Child<'_>>
+    )
+    {
+        let name = element.name();
+
+        write!(out, "<{name}").unwrap();
+
+        for tag in element.tags()
+        {
+            out.push(' ');
+            Self::write_tag(out, tag);
+        }
+
+        out.push('>');
+
+        if element.is_childless()
+        {
+            return;
+        }
+
+        if is_verbatim(name)
+        {
+            self.write_verbatim(out, element.children());
+        } else
+        {
+            self.write_children(out, element.children(), Some(element));
+        }
+
+        if !(self.omit_ends && can_omit_end(name, parent, next))
+        {
+            write!(out, "</{name}>").unwrap();
+        }
+    }
+
+    // pre and listing can have elements in them, all of it is written out like it was in the source
+    fn write_verbatim(&self, out: &mut String, children: &[Child<'_>])
+    {
+        for child in children
+        {
+            match child
+            {
+                Child::Element(element) =>
+                {
+                    let name = element.name();
+
+                    write!(out, "<{name}").unwrap();
+
+                    for tag in element.tags()
+                    {
+                        out.push(' ');
+                        Self::write_tag(out, tag);
+                    }
+
+                    out.push('>');
+
+                    if !element.is_childless()
+                    {
+                        self.write_verbatim(out, element.children());
+
+                        write!(out, "</{name}>").unwrap();
+                    }
+                },
+                Child::Text(text) => out.push_str(text.raw()),
+                Child::Comment(comment) =>
+                {
+                    if self.keep_comments
+                    {
+                        write!(out, "{comment}").unwrap();
+                    }
+                },
+                Child::ProcessingInstruction(instruction) => write!(out, "{instruction}").unwrap()
+            }
+        }
+    }
+
+    fn write_tag(out: &mut String, tag: &Tag<'_>)
+    {
+        out.push_str(tag.name());
+
+        let value = match tag.raw_content()
+        {
+            Some(x) if !x.is_empty() && !is_boolean(tag) => x,
+            _ => return
+        };
+
+        if can_unquote(value)
+        {
+            write!(out, "={value}").unwrap();
+        } else if !value.contains('"')
+        {
+            write!(out, "=\"{value}\"").unwrap();
+        } else if !value.contains('\'')
+        {
+            write!(out, "='{value}'").unwrap();
+        } else
+        {
+            write!(out, "=\"{}\"", value.replace('"', "&quot;")).unwrap();
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    mem,
+    borrow::Cow,
+    ops::Range
+};
+
+use super::{
+    Child,
+    Element,
+    Document,
+    Tag,
+    Span,
+    Attributes,
+    Namespace,
+    MutationError,
+    MutationErrorKind,
+    tree_builder::is_childless
+};
+
+
+impl<'a> Child<'a>
+{
+    pub fn element_mut(&mut self) -> Option<&mut Element<'a>>
+    {
+        match self
+        {
+            Child::Element(ref mut element) => Some(element),
+            _ => None
+        }
+    }
+}
+
+impl<'a> Element<'a>
+{
+    // an empty element that wasnt in the text, so its span is empty too
+    pub fn new(name: impl Into<Cow<'a, str>>) -> Self
+    {
+        Self{
+            name: name.into(),
+            spelling: None,
+            namespace: Namespace::Html,
+            namespace_uri: None,
+            span: Span::default(),
+            tags: Attributes::new(),
+            children: Vec::new(),
+            is_modified: false,
+            is_reconstructed: false
+        }
+    }
+
+    // like new but for svg or mathml
+    pub fn with_namespace(name: impl Into<Cow<'a, str>>, namespace: Namespace) -> Self
+    {
+        Self{namespace, ..Self::new(name)}
+    }
+
+    // an element with children cant become a childless one
+    pub fn rename(&mut self, name: impl Into<Cow<'a, str>>) -> Result<(), MutationError>
+    {
+        let name = name.into();
+
+        if !self.is_xml() && is_childless(&name) && !self.children.is_empty()
+        {
+            return Err(MutationError::new(MutationErrorKind::Childless, name, None));
+        }
+
+        self.name = name;
+        self.spelling = None;
+        self.is_modified = true;
+
+        Ok(())
+    }
+
+    // replaces the tag with the same name in place if theres one, returns the old one
+    pub fn set_tag(&mut self, tag: Tag<'a>) -> Option<Tag<'a>>
+    {
+        self.is_modified = true;
+
+        self.tags.set(tag)
+    }
+
+    pub fn remove_tag(&mut self, name: &str) -> Option<Tag<'a>>
+    {
+        let tag = self.tags.remove(name)?;
+
+        self.is_modified = true;
+
+        Some(tag)
+    }
+
+    // the child might get replaced through this so it counts as a change
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Child<'a>>
+    {
+        self.is_modified = true;
+
+        self.children.get_mut(index)
+    }
+
+    pub fn append(&mut self, child: Child<'a>) -> Result<(), MutationError>
+    {
+        self.insert(self.children.len(), child)
+    }
+
+    // index can be one past the last child
+    pub fn insert(&mut self, index: usize, child: Child<'a>) -> Result<(), MutationError>
+    {
+        self.check_childless()?;
+
+        if index > self.children.len()
+        {
+            return Err(self.out_of_bounds(index));
+        }
+
+        self.children.insert(index, child);
+        self.is_modified = true;
+
+        Ok(())
+    }
+
+    // moving a subtree somewhere else is removing it and inserting it there
+    pub fn remove(&mut self, index: usize) -> Result<Child<'a>, MutationError>
+    {
+        self.check_index(index)?;
+
+        self.is_modified = true;
+
+        Ok(self.children.remove(index))
+    }
+
+    pub fn replace(&mut self, index: usize, child: Child<'a>) -> Result<Child<'a>, MutationError>
+    {
+        self.check_index(index)?;
+
+        self.is_modified = true;
+
+        Ok(mem::replace(&mut self.children[index], child))
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&Child<'a>) -> bool)
+    {
+        self.children.retain(f);
+        self.is_modified = true;
+    }
+
+    pub fn take_children(&mut self) -> Vec<Child<'a>>
+    {
+        self.is_modified = true;
+
+        mem::take(&mut self.children)
+    }
+
+    // moves a child to a different index, to is where it ends up after the move
+    pub fn move_child(&mut self, from: usize, to: usize) -> Result<(), MutationError>
+    {
+        self.check_index(from)?;
+        self.check_index(to)?;
+
+        let child = self.children.remove(from);
+        self.children.insert(to, child);
+
+        self.is_modified = true;
+
+        Ok(())
+    }
+
+    // the children in the range go after whatever the wrapper already has, the wrapper takes their place
+    pub fn wrap(&mut self, range: Range<usize>, mut wrapper: Element<'a>) -> Result<(), MutationError>
+    {
+        if range.start > range.end || range.end > self.children.len()
+        {
+            return Err(self.out_of_bounds(range.end));
+        }
+
+        if !range.is_empty()
+        {
+            wrapper.check_childless()?;
+        }
+
+        let index = range.start;
+        wrapper.children.extend(self.children.drain(range));
+
+        wrapper.is_modified = true;
+
+        self.children.insert(index, Child::Element(wrapper));
+        self.is_modified = true;
+
+        Ok(())
+    }
+
+    // puts the children of the element at index in its place, returns the now empty element
+    pub fn unwrap_child(&mut self, index: usize) -> Result<Element<'a>, MutationError>
+    {
+        self.check_index(index)?;
+
+        let mut element = match self.children.remove(index)
+        {
+            Child::Element(element) => element,
+            child =>
+            {
+                self.children.insert(index, child);
+
+                return Err(MutationError::new(MutationErrorKind::NotAnElement, self.name(), Some(index)));
+            }
+        };
+
+        let children = element.take_children();
+        self.children.splice(index..index, children);
+
+        self.is_modified = true;
+
+        Ok(element)
+    }
+
+    fn check_childless(&self) -> Result<(), MutationError>
+    {
+        if self.is_childless()
+        {
+            Err(MutationError::new(MutationErrorKind::Childless, self.name(), None))
+        } else
+        {
+            Ok(())
+        }
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), MutationError>
+    {
+        if index < self.children.len()
+        {
+            Ok(())
+        } else
+        {
+            Err(self.out_of_bounds(index))
+        }
+    }
+
+    fn out_of_bounds(&self, index: usize) -> MutationError
+    {
+        MutationError::new(MutationErrorKind::OutOfBounds, self.name(), Some(index))
+    }
+}
+
+impl<'a> Document<'a>
+{
+    pub fn root_mut(&mut self) -> Option<&mut Element<'a>>
+    {
+        self.is_modified = true;
+
+        self.children.iter_mut().find_map(|child| child.element_mut())
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Child<'a>>
+    {
+        self.is_modified = true;
+
+        &mut self.children
+    }
+}
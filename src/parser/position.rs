@@ -0,0 +1,72 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position
+{
+    pub line: u64,
+    pub column: u64,
+    // in bytes from the start of the text
+    pub offset: usize
+}
+
+impl Default for Position
+{
+    fn default() -> Self
+    {
+        Self{line: 1, column: 1, offset: 0}
+    }
+}
+
+impl Position
+{
+    pub fn advance(&mut self, c: char)
+    {
+        self.offset += c.len_utf8();
+
+        if c == '\n'
+        {
+            self.line += 1;
+            self.column = 1;
+        } else
+        {
+            self.column += 1;
+        }
+    }
+
+    pub fn advanced(mut self, text: &str) -> Self
+    {
+        text.chars().for_each(|c| self.advance(c));
+
+        self
+    }
+}
+
+// from start up to (but not including) end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span
+{
+    pub start: Position,
+    pub end: Position
+}
+
+impl Span
+{
+    pub fn new(start: Position, end: Position) -> Self
+    {
+        Self{start, end}
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    // the part of the original text this span covers
+    pub fn slice<'a>(&self, text: &'a str) -> Option<&'a str>
+    {
+        text.get(self.start.offset..self.end.offset)
+    }
+}
@@ -0,0 +1,558 @@
+use std::{
+    ptr,
+    str::FromStr
+};
+
+use super::{
+    Child,
+    Element,
+    Document,
+    SelectorError
+};
+
+mod parse;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator
+{
+    Descendant,
+    Child,
+    Adjacent,
+    Sibling
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeOperator
+{
+    // =
+    Equals,
+    // ~=
+    Includes,
+    // |=
+    DashMatch,
+    // ^=
+    Prefix,
+    // $=
+    Suffix,
+    // *=
+    Substring
+}
+
+impl AttributeOperator
+{
+    fn matches(&self, value: &str, expected: &str) -> bool
+    {
+        match self
+        {
+            Self::Equals => value == expected,
+            Self::Includes => value.split_ascii_whitespace().any(|word| word == expected),
+            Self::DashMatch =>
+            {
+                value == expected
+                    || value.strip_prefix(expected).is_some_and(|rest| rest.starts_with('-'))
+            },
+            Self::Prefix => !expected.is_empty() && value.starts_with(expected),
+            Self::Suffix => !expected.is_empty() && value.ends_with(expected),
+            Self::Substring => !expected.is_empty() && value.contains(expected)
+        }
+    }
+}
+
+// an+b, the positions r counted from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nth
+{
+    pub a: i64,
+    pub b: i64
+}
+
+impl Nth
+{
+    pub fn matches(&self, position: usize) -> bool
+    {
+        // wide enough that no a and b can overflow it
+        let (a, b, position) = (self.a as i128, self.b as i128, position as i128);
+
+        if a == 0
+        {
+            return position == b;
+        }
+
+        let difference = position - b;
+
+        difference % a == 0 && difference / a >= 0
+    }
+}
+
+pub type SelectorList = Box<[Complex]>;
+
+#[derive(Debug, Clone)]
+pub enum PseudoClass
+{
+    Root,
+    Empty,
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    FirstOfType,
+    LastOfType,
+    OnlyOfType,
+    NthChild(Nth, Option<SelectorList>),
+    NthLastChild(Nth, Option<SelectorList>),
+    NthOfType(Nth),
+    NthLastOfType(Nth),
+    Not(Box<[Complex]>),
+    // :is and :where, specificity doesnt matter here
+    Is(Box<[Complex]>),
+    // each one starts with the element the :has is on
+    Has(Box<[Complex]>)
+}
+
+#[derive(Debug, Clone)]
+pub enum Simple
+{
+    Type(String),
+    Id(String),
+    Class(String),
+    Attribute{
+        name: String,
+        value: Option<(AttributeOperator, String)>,
+        ignore_case: bool
+    },
+    Pseudo(PseudoClass),
+    // the element a relative selector is relative to
+    Anchor
+}
+
+// everything without a combinator between it, empty is *
+#[derive(Debug, Clone, Default)]
+pub struct Compound
+{
+    pub parts: Vec<Simple>
+}
+
+// the combinator is the one to the left of the compound, the first one doesnt matter
+#[derive(Debug, Clone)]
+pub struct Complex
+{
+    pub parts: Vec<(Combinator, Compound)>
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<'b, 'a>
+{
+    element: &'b Element<'a>,
+    // in the children of its parent
+    index: usize
+}
+
+// an element along with everything needed to look at its parents and siblings
+#[derive(Debug, Clone, Copy)]
+struct Cursor<'p, 'b, 'a>
+{
+    ancestors: &'p [Entry<'b, 'a>],
+    // the siblings of the topmost ancestor, if its in a document
+    top: Option<&'b [Child<'a>]>,
+    entry: Entry<'b, 'a>
+}
+
+impl<'p, 'b, 'a> Cursor<'p, 'b, 'a>
+{
+    fn element(&self) -> &'b Element<'a>
+    {
+        self.entry.element
+    }
+
+    fn siblings(&self) -> Option<&'b [Child<'a>]>
+    {
+        match self.ancestors.last()
+        {
+            Some(parent) => Some(parent.element.children()),
+            None => self.top
+        }
+    }
+
+    fn parent(&self) -> Option<Self>
+    {
+        let (entry, ancestors) = self.ancestors.split_last()?;
+
+        Some(Self{ancestors, top: self.top, entry: *entry})
+    }
+
+    fn with_entry(&self, entry: Entry<'b, 'a>) -> Self
+    {
+        Self{entry, ..*self}
+    }
+
+    // the element siblings before this one, closest first
+    fn preceding(&self) -> impl Iterator<Item=Self> + '_
+    {
+        let siblings = self.siblings().unwrap_or_default();
+
+        siblings[..self.entry.index.min(siblings.len())].iter().enumerate().rev().filter_map(move |(index, child)|
+        {
+            child.element().map(|element| self.with_entry(Entry{element, index}))
+        })
+    }
+
+    fn following(&self) -> impl Iterator<Item=Self> + '_
+    {
+        let siblings = self.siblings().unwrap_or_default();
+        let start = (self.entry.index + 1).min(siblings.len());
+
+        siblings[start..].iter().enumerate().filter_map(move |(index, child)|
+        {
+            child.element().map(|element| self.with_entry(Entry{element, index: start + index}))
+        })
+    }
+}
+
+fn matches_compound(compound: &Compound, cursor: &Cursor, anchor: Option<&Element>) -> bool
+{
+    compound.parts.iter().all(|simple| matches_simple(simple, cursor, anchor))
+}
+
+fn matches_simple(simple: &Simple, cursor: &Cursor, anchor: Option<&Element>) -> bool
+{
+    let element = cursor.element();
+
+    match simple
+    {
+        Simple::Type(name) => element.is_named(name),
+        Simple::Id(id) => element.id() == Some(id.as_str()),
+        Simple::Class(class) => element.has_class(class),
+        Simple::Attribute{name, value, ignore_case} =>
+        {
+            let found = match element.attr(name)
+            {
+                Some(x) => x,
+                None => return false
+            };
+
+            match value
+            {
+                None => true,
+                Some((operator, expected)) if *ignore_case =>
+                {
+                    operator.matches(&found.to_lowercase(), &expected.to_lowercase())
+                },
+                Some((operator, expected)) => operator.matches(found, expected)
+            }
+        },
+        Simple::Pseudo(pseudo) => matches_pseudo(pseudo, cursor, anchor),
+        Simple::Anchor => anchor.is_some_and(|anchor| ptr::eq(anchor, element))
+    }
+}
+
+fn is_same_type(a: &Element, b: &Element) -> bool
+{
+    a.namespace() == b.namespace() && a.is_named(b.name())
+}
+
+fn matches_pseudo(pseudo: &PseudoClass, cursor: &Cursor, anchor: Option<&Element>) -> bool
+{
+    let element = cursor.element();
+    let of_type = |other: &Cursor| is_same_type(other.element(), element);
+
+    let matching = |other: &Cursor, list: &Option<SelectorList>|
+    {
+        list.as_ref().map(|list| matches_any(list, other, anchor)).unwrap_or(true)
+    };
+
+    match pseudo
+    {
+        PseudoClass::Root => cursor.ancestors.is_empty(),
+        PseudoClass::Empty =>
+        {
+            element.children().iter().all(|child| child.is_comment())
+        },
+        PseudoClass::FirstChild => cursor.preceding().next().is_none(),
+        PseudoClass::LastChild => cursor.following().next().is_none(),
+        PseudoClass::OnlyChild => cursor.preceding().next().is_none() && cursor.following().next().is_none(),
+        PseudoClass::FirstOfType => !cursor.preceding().any(|x| of_type(&x)),
+        PseudoClass::LastOfType => !cursor.following().any(|x| of_type(&x)),
+        PseudoClass::OnlyOfType => !cursor.preceding().any(|x| of_type(&x)) && !cursor.following().any(|x| of_type(&x)),
+        PseudoClass::NthChild(nth, list) =>
+        {
+            matching(cursor, list) && nth.matches(cursor.preceding().filter(|x| matching(x, list)).count() + 1)
+        },
+        PseudoClass::NthLastChild(nth, list) =>
+        {
+            matching(cursor, list) && nth.matches(cursor.following().filter(|x| matching(x, list)).count() + 1)
+        },
+        PseudoClass::NthOfType(nth) => nth.matches(cursor.preceding().filter(of_type).count() + 1),
+        PseudoClass::NthLastOfType(nth) => nth.matches(cursor.following().filter(of_type).count() + 1),
+        PseudoClass::Not(list) => !matches_any(list, cursor, anchor),
+        PseudoClass::Is(list) => matches_any(list, cursor, anchor),
+        PseudoClass::Has(list) => matches_has(list, cursor)
+    }
+}
+
+fn matches_any(list: &[Complex], cursor: &Cursor, anchor: Option<&Element>) -> bool
+{
+    list.iter().any(|complex| matches_complex(&complex.parts, cursor, anchor))
+}
+
+// goes from the rightmost compound to the left
+fn matches_complex(parts: &[(Combinator, Compound)], cursor: &Cursor, anchor: Option<&Element>) -> bool
+{
+    let ((combinator, compound), rest) = match parts.split_last()
+    {
+        Some(x) => x,
+        None => return false
+    };
+
+    if !matches_compound(compound, cursor, anchor)
+    {
+        return false;
+    }
+
+    if rest.is_empty()
+    {
+        return true;
+    }
+
+    match combinator
+    {
+        Combinator::Child => cursor.parent().is_some_and(|parent| matches_complex(rest, &parent, anchor)),
+        Combinator::Descendant =>
+        {
+            let mut current = cursor.parent();
+            while let Some(parent) = current
+            {
+                if matches_complex(rest, &parent, anchor)
+                {
+                    return true;
+                }
+
+                current = parent.parent();
+            }
+
+            false
+        },
+        Combinator::Adjacent => cursor.preceding().next().is_some_and(|x| matches_complex(rest, &x, anchor)),
+        Combinator::Sibling => cursor.preceding().any(|x| matches_complex(rest, &x, anchor))
+    }
+}
+
+fn matches_has(list: &[Complex], cursor: &Cursor) -> bool
+{
+    let anchor = Some(cursor.element());
+
+    // relative selectors can only reach things after or inside of the anchor
+    let is_sibling = list.iter().any(|complex|
+    {
+        complex.parts.get(1).is_some_and(|(combinator, _)|
+        {
+            matches!(combinator, Combinator::Adjacent | Combinator::Sibling)
+        })
+    });
+
+    let mut check = |candidate: &Cursor| matches_any(list, candidate, anchor);
+
+    let mut ancestors = cursor.ancestors.to_vec();
+    ancestors.push(cursor.entry);
+
+    if walk(&mut ancestors, cursor.top, &mut check)
+    {
+        return true;
+    }
+
+    if is_sibling
+    {
+        ancestors.pop();
+
+        for sibling in cursor.following()
+        {
+            if check(&sibling)
+            {
+                return true;
+            }
+
+            ancestors.push(sibling.entry);
+
+            if walk(&mut ancestors, cursor.top, &mut check)
+            {
+                return true;
+            }
+
+            ancestors.pop();
+        }
+    }
+
+    false
+}
+
+// goes through every element inside of the last ancestor in document order, stops once f returns true
+fn walk<'b, 'a>(
+    ancestors: &mut Vec<Entry<'b, 'a>>,
+    top: Option<&'b [Child<'a>]>,
+    f: &mut impl FnMut(&Cursor<'_, 'b, 'a>) -> bool
+) -> bool
+{
+    let parent = match ancestors.last()
+    {
+        Some(x) => x.element,
+        None => return false
+    };
+
+    for (index, child) in parent.children().iter().enumerate()
+    {
+        if let Child::Element(element) = child
+        {
+            let entry = Entry{element, index};
+
+            if f(&Cursor{ancestors, top, entry})
+            {
+                return true;
+            }
+
+            ancestors.push(entry);
+            let is_done = walk(ancestors, top, f);
+            ancestors.pop();
+
+            if is_done
+            {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+#[derive(Debug, Clone)]
+pub struct Selector
+{
+    list: Box<[Complex]>
+}
+
+impl Selector
+{
+    pub fn parse(text: &str) -> Result<Self, SelectorError>
+    {
+        parse::parse_list(text).map(|list| Self{list: list.into_boxed_slice()})
+    }
+
+    pub fn complexes(&self) -> &[Complex]
+    {
+        &self.list
+    }
+
+    // matching elements inside of the element in document order, not including itself
+    pub fn select<'b, 'a>(&self, element: &'b Element<'a>) -> Vec<&'b Element<'a>>
+    {
+        let mut found = Vec::new();
+        self.select_in(element, None, |element| { found.push(element); false });
+
+        found
+    }
+
+    pub fn select_first<'b, 'a>(&self, element: &'b Element<'a>) -> Option<&'b Element<'a>>
+    {
+        let mut found = None;
+        self.select_in(element, None, |element| { found = Some(element); true });
+
+        found
+    }
+
+    pub fn select_document<'b, 'a>(&self, document: &'b Document<'a>) -> Vec<&'b Element<'a>>
+    {
+        let mut found = Vec::new();
+        self.select_top(document.children(), |element| { found.push(element); false });
+
+        found
+    }
+
+    pub fn select_document_first<'b, 'a>(&self, document: &'b Document<'a>) -> Option<&'b Element<'a>>
+    {
+        let mut found = None;
+        self.select_top(document.children(), |element| { found = Some(element); true });
+
+        found
+    }
+
+    fn select_in<'b, 'a>(
+        &self,
+        element: &'b Element<'a>,
+        top: Option<&'b [Child<'a>]>,
+        mut f: impl FnMut(&'b Element<'a>) -> bool
+    )
+    {
+        let mut ancestors = vec![Entry{element, index: 0}];
+
+        walk(&mut ancestors, top, &mut |cursor|
+        {
+            matches_any(&self.list, cursor, None) && f(cursor.element())
+        });
+    }
+
+    fn select_top<'b, 'a>(&self, children: &'b [Child<'a>], mut f: impl FnMut(&'b Element<'a>) -> bool)
+    {
+        for (index, child) in children.iter().enumerate()
+        {
+            if let Child::Element(element) = child
+            {
+                let entry = Entry{element, index};
+                let cursor = Cursor{ancestors: &[], top: Some(children), entry};
+
+                if matches_any(&self.list, &cursor, None) && f(element)
+                {
+                    return;
+                }
+
+                let mut ancestors = vec![entry];
+
+                let mut is_done = false;
+                walk(&mut ancestors, Some(children), &mut |cursor|
+                {
+                    is_done = matches_any(&self.list, cursor, None) && f(cursor.element());
+
+                    is_done
+                });
+
+                if is_done
+                {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Selector
+{
+    type Err = SelectorError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err>
+    {
+        Self::parse(text)
+    }
+}
+
+impl<'a> Element<'a>
+{
+    pub fn select(&self, selector: &str) -> Result<Vec<&Element<'a>>, SelectorError>
+    {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+
+    pub fn select_first(&self, selector: &str) -> Result<Option<&Element<'a>>, SelectorError>
+    {
+        Ok(Selector::parse(selector)?.select_first(self))
+    }
+}
+
+impl<'a> Document<'a>
+{
+    pub fn select(&self, selector: &str) -> Result<Vec<&Element<'a>>, SelectorError>
+    {
+        Ok(Selector::parse(selector)?.select_document(self))
+    }
+
+    pub fn select_first(&self, selector: &str) -> Result<Option<&Element<'a>>, SelectorError>
+    {
+        Ok(Selector::parse(selector)?.select_document_first(self))
+    }
+}
@@ -0,0 +1,513 @@
+use std::{
+    str::CharIndices,
+    iter::Peekable
+};
+
+use super::{
+    Combinator,
+    AttributeOperator,
+    Nth,
+    PseudoClass,
+    Simple,
+    Compound,
+    Complex,
+    SelectorList
+};
+
+use crate::parser::{
+    SelectorError,
+    SelectorErrorKind
+};
+
+
+fn is_name_char(c: char) -> bool
+{
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+pub fn parse_list(text: &str) -> Result<Vec<Complex>, SelectorError>
+{
+    let mut parser = SelectorParser::new(text);
+
+    let list = parser.parse_list(false)?;
+
+    match parser.peek()
+    {
+        None => Ok(list),
+        Some(c) => Err(parser.unexpected(c))
+    }
+}
+
+struct SelectorParser<'a>
+{
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>
+}
+
+impl<'a> SelectorParser<'a>
+{
+    fn new(text: &'a str) -> Self
+    {
+        Self{text, chars: text.char_indices().peekable()}
+    }
+
+    fn offset(&mut self) -> usize
+    {
+        self.chars.peek().map(|(offset, _)| *offset).unwrap_or(self.text.len())
+    }
+
+    fn peek(&mut self) -> Option<char>
+    {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn next(&mut self) -> Option<char>
+    {
+        self.chars.next().map(|(_, c)| c)
+    }
+
+    fn error(&mut self, kind: SelectorErrorKind, found: impl Into<String>) -> SelectorError
+    {
+        let offset = self.offset();
+
+        SelectorError::new(kind, found, offset)
+    }
+
+    fn unexpected(&mut self, c: char) -> SelectorError
+    {
+        self.error(SelectorErrorKind::UnexpectedChar, c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SelectorError>
+    {
+        match self.peek()
+        {
+            Some(c) if c == expected =>
+            {
+                self.next();
+
+                Ok(())
+            },
+            Some(c) => Err(self.unexpected(c)),
+            None => Err(self.error(SelectorErrorKind::UnexpectedEnd, ""))
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool
+    {
+        let mut skipped = false;
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some()
+        {
+            skipped = true;
+        }
+
+        skipped
+    }
+
+    // relative lists r the ones inside of :has, they can start with a combinator
+    fn parse_list(&mut self, is_relative: bool) -> Result<Vec<Complex>, SelectorError>
+    {
+        let mut list = Vec::new();
+
+        loop
+        {
+            self.skip_whitespace();
+
+            list.push(self.parse_complex(is_relative)?);
+
+            self.skip_whitespace();
+
+            if self.peek() == Some(',')
+            {
+                self.next();
+            } else
+            {
+                return Ok(list);
+            }
+        }
+    }
+
+    fn parse_combinator(&mut self) -> Option<Combinator>
+    {
+        let combinator = match self.peek()?
+        {
+            '>' => Combinator::Child,
+            '+' => Combinator::Adjacent,
+            '~' => Combinator::Sibling,
+            _ => return None
+        };
+
+        self.next();
+
+        Some(combinator)
+    }
+
+    fn parse_complex(&mut self, is_relative: bool) -> Result<Complex, SelectorError>
+    {
+        let mut parts = Vec::new();
+
+        if is_relative
+        {
+            let anchor = Compound{parts: vec![Simple::Anchor]};
+            parts.push((Combinator::Descendant, anchor));
+
+            let combinator = self.parse_combinator().unwrap_or(Combinator::Descendant);
+            self.skip_whitespace();
+
+            parts.push((combinator, self.parse_compound()?));
+        } else
+        {
+            parts.push((Combinator::Descendant, self.parse_compound()?));
+        }
+
+        loop
+        {
+            let is_whitespace = self.skip_whitespace();
+
+            let combinator = match self.parse_combinator()
+            {
+                Some(x) => x,
+                None => match self.peek()
+                {
+                    Some(',' | ')') | None => return Ok(Complex{parts}),
+                    Some(_) if is_whitespace => Combinator::Descendant,
+                    Some(c) => return Err(self.unexpected(c))
+                }
+            };
+
+            self.skip_whitespace();
+
+            parts.push((combinator, self.parse_compound()?));
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, SelectorError>
+    {
+        let mut parts = Vec::new();
+        let mut is_universal = false;
+
+        match self.peek()
+        {
+            Some('*') =>
+            {
+                self.next();
+
+                is_universal = true;
+            },
+            Some(c) if is_name_char(c) || c == '\\' =>
+            {
+                parts.push(Simple::Type(self.parse_name()?));
+            },
+            _ => ()
+        }
+
+        loop
+        {
+            let simple = match self.peek()
+            {
+                Some('#') =>
+                {
+                    self.next();
+
+                    Simple::Id(self.parse_name()?)
+                },
+                Some('.') =>
+                {
+                    self.next();
+
+                    Simple::Class(self.parse_name()?)
+                },
+                Some('[') => self.parse_attribute()?,
+                Some(':') => Simple::Pseudo(self.parse_pseudo()?),
+                _ => break
+            };
+
+            parts.push(simple);
+        }
+
+        if parts.is_empty() && !is_universal
+        {
+            return Err(match self.peek()
+            {
+                Some(c) => self.unexpected(c),
+                None => self.error(SelectorErrorKind::UnexpectedEnd, "")
+            });
+        }
+
+        Ok(Compound{parts})
+    }
+
+    fn parse_name(&mut self) -> Result<String, SelectorError>
+    {
+        let mut name = String::new();
+
+        while let Some(c) = self.peek()
+        {
+            if c == '\\'
+            {
+                self.next();
+
+                match self.next()
+                {
+                    Some(c) => name.push(c),
+                    None => return Err(self.error(SelectorErrorKind::UnexpectedEnd, "\\"))
+                }
+            } else if is_name_char(c)
+            {
+                self.next();
+                name.push(c);
+            } else
+            {
+                break;
+            }
+        }
+
+        if name.is_empty()
+        {
+            return match self.peek()
+            {
+                Some(c) => Err(self.unexpected(c)),
+                None => Err(self.error(SelectorErrorKind::UnexpectedEnd, ""))
+            };
+        }
+
+        Ok(name)
+    }
+
+    fn parse_string(&mut self) -> Result<String, SelectorError>
+    {
+        let quote = match self.peek()
+        {
+            Some(c @ ('"' | '\'')) => c,
+            _ => return self.parse_name()
+        };
+
+        self.next();
+
+        let mut value = String::new();
+        loop
+        {
+            match self.next()
+            {
+                Some('\\') =>
+                {
+                    if let Some(c) = self.next()
+                    {
+                        value.push(c);
+                    }
+                },
+                Some(c) if c == quote => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error(SelectorErrorKind::UnexpectedEnd, quote))
+            }
+        }
+    }
+
+    fn parse_attribute(&mut self) -> Result<Simple, SelectorError>
+    {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        let name = self.parse_name()?;
+
+        self.skip_whitespace();
+
+        let operator = match self.peek()
+        {
+            Some(']') =>
+            {
+                self.next();
+
+                return Ok(Simple::Attribute{name, value: None, ignore_case: false});
+            },
+            Some('=') => None,
+            Some('~') => Some(AttributeOperator::Includes),
+            Some('|') => Some(AttributeOperator::DashMatch),
+            Some('^') => Some(AttributeOperator::Prefix),
+            Some('$') => Some(AttributeOperator::Suffix),
+            Some('*') => Some(AttributeOperator::Substring),
+            Some(c) => return Err(self.unexpected(c)),
+            None => return Err(self.error(SelectorErrorKind::UnexpectedEnd, ""))
+        };
+
+        if operator.is_some()
+        {
+            self.next();
+        }
+
+        self.expect('=')?;
+        self.skip_whitespace();
+
+        let value = self.parse_string()?;
+
+        self.skip_whitespace();
+
+        let ignore_case = match self.peek()
+        {
+            Some('i' | 'I') =>
+            {
+                self.next();
+
+                true
+            },
+            Some('s' | 'S') =>
+            {
+                self.next();
+
+                false
+            },
+            _ => false
+        };
+
+        self.skip_whitespace();
+        self.expect(']')?;
+
+        let operator = operator.unwrap_or(AttributeOperator::Equals);
+
+        Ok(Simple::Attribute{name, value: Some((operator, value)), ignore_case})
+    }
+
+    fn parse_pseudo(&mut self) -> Result<PseudoClass, SelectorError>
+    {
+        self.expect(':')?;
+
+        if self.peek() == Some(':')
+        {
+            return Err(self.error(SelectorErrorKind::Unsupported, "pseudo element"));
+        }
+
+        let offset = self.offset();
+        let name = self.parse_name()?.to_ascii_lowercase();
+
+        let simple = match name.as_ref()
+        {
+            "root" => PseudoClass::Root,
+            "empty" => PseudoClass::Empty,
+            "first-child" => PseudoClass::FirstChild,
+            "last-child" => PseudoClass::LastChild,
+            "only-child" => PseudoClass::OnlyChild,
+            "first-of-type" => PseudoClass::FirstOfType,
+            "last-of-type" => PseudoClass::LastOfType,
+            "only-of-type" => PseudoClass::OnlyOfType,
+            "nth-child" | "nth-last-child" =>
+            {
+                let (nth, list) = self.parenthesized(|this| this.parse_nth(true))?;
+
+                if name == "nth-child"
+                {
+                    PseudoClass::NthChild(nth, list)
+                } else
+                {
+                    PseudoClass::NthLastChild(nth, list)
+                }
+            },
+            "nth-of-type" => PseudoClass::NthOfType(self.parenthesized(|this| this.parse_nth(false))?.0),
+            "nth-last-of-type" => PseudoClass::NthLastOfType(self.parenthesized(|this| this.parse_nth(false))?.0),
+            "not" => PseudoClass::Not(self.parenthesized(|this| this.parse_list(false))?.into_boxed_slice()),
+            "is" | "where" => PseudoClass::Is(self.parenthesized(|this| this.parse_list(false))?.into_boxed_slice()),
+            "has" => PseudoClass::Has(self.parenthesized(|this| this.parse_list(true))?.into_boxed_slice()),
+            _ => return Err(SelectorError::new(SelectorErrorKind::UnknownPseudoClass, name, offset))
+        };
+
+        Ok(simple)
+    }
+
+    fn parenthesized<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, SelectorError>
+    ) -> Result<T, SelectorError>
+    {
+        self.expect('(')?;
+        self.skip_whitespace();
+
+        let value = f(self)?;
+
+        self.skip_whitespace();
+        self.expect(')')?;
+
+        Ok(value)
+    }
+
+    // an+b, optionally followed by of and a selector list
+    fn parse_nth(&mut self, allow_of: bool) -> Result<(Nth, Option<SelectorList>), SelectorError>
+    {
+        let offset = self.offset();
+
+        let mut expression = String::new();
+        let mut list = None;
+        while let Some(c) = self.peek().filter(|&c| c != ')')
+        {
+            // the list goes through the normal parser so parentheses in it work
+            let rest = &self.text[self.offset()..];
+            let is_of = expression.ends_with(char::is_whitespace)
+                && rest.strip_prefix("of").is_some_and(|rest| rest.starts_with(char::is_whitespace));
+
+            if allow_of && is_of
+            {
+                self.next();
+                self.next();
+
+                list = Some(self.parse_list(false)?.into_boxed_slice());
+
+                break;
+            }
+
+            self.next();
+
+            expression.push(c);
+        }
+
+        let nth = parse_nth_expression(&expression)
+            .ok_or_else(|| SelectorError::new(SelectorErrorKind::InvalidNth, expression.trim(), offset))?;
+
+        Ok((nth, list))
+    }
+}
+
+fn parse_nth_expression(expression: &str) -> Option<Nth>
+{
+    let expression = expression.trim().to_ascii_lowercase();
+
+    match expression.as_ref()
+    {
+        "odd" => return Some(Nth{a: 2, b: 1}),
+        "even" => return Some(Nth{a: 2, b: 0}),
+        _ => ()
+    }
+
+    let expression = expression.split_whitespace().collect::<String>();
+
+    match expression.split_once('n')
+    {
+        Some((a, b)) =>
+        {
+            let a = match a
+            {
+                "" | "+" => 1,
+                "-" => -1,
+                a => a.parse().ok()?
+            };
+
+            // exactly one sign and then only digits, so no 2n+-3 or 2n++3
+            let b = match b.split_at_checked(1)
+            {
+                None => 0,
+                Some((sign, digits)) if !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit()) =>
+                {
+                    let b: i64 = digits.parse().ok()?;
+
+                    match sign
+                    {
+                        "+" => b,
+                        "-" => -b,
+                        _ => return None
+                    }
+                },
+                _ => return None
+            };
+
+            Some(Nth{a, b})
+        },
+        None => Some(Nth{a: 0, b: expression.parse().ok()?})
+    }
+}
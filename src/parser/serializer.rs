@@ -0,0 +1,345 @@
+use std::{
+    fmt,
+    io
+};
+
+use super::{
+    Child,
+    Comment,
+    Element,
+    Document,
+    Doctype,
+    XmlDeclaration,
+    ProcessingInstruction,
+    Tag,
+    Text,
+    Span
+};
+
+use super::syntaxer::lexer::is_raw_text;
+
+
+// xml only knows the predefined entities so nbsp stays as it is there
+fn escape(out: &mut impl fmt::Write, text: &str, is_attribute: bool, is_xml: bool) -> fmt::Result
+{
+    for c in text.chars()
+    {
+        match c
+        {
+            '&' => out.write_str("&amp;")?,
+            '\u{a0}' if !is_xml => out.write_str("&nbsp;")?,
+            '<' => out.write_str("&lt;")?,
+            '>' => out.write_str("&gt;")?,
+            '"' if is_attribute => out.write_str("&quot;")?,
+            c => out.write_char(c)?
+        }
+    }
+
+    Ok(())
+}
+
+// lets io::Write be used as fmt::Write, keeping the actual error around
+struct IoWriter<W>
+{
+    inner: W,
+    error: Option<io::Error>
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W>
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result
+    {
+        self.inner.write_all(s.as_bytes()).map_err(|err|
+        {
+            self.error = Some(err);
+
+            fmt::Error
+        })
+    }
+}
+
+pub trait Serialize
+{
+    fn serialize(&self, serializer: &Serializer<'_>, out: &mut impl fmt::Write) -> fmt::Result;
+}
+
+impl<'a> Serialize for Element<'a>
+{
+    fn serialize(&self, serializer: &Serializer<'_>, out: &mut impl fmt::Write) -> fmt::Result
+    {
+        serializer.write_element(out, self)
+    }
+}
+
+impl<'a> Serialize for Child<'a>
+{
+    fn serialize(&self, serializer: &Serializer<'_>, out: &mut impl fmt::Write) -> fmt::Result
+    {
+        serializer.write_child(out, self, None)
+    }
+}
+
+impl<'a> Serialize for Document<'a>
+{
+    fn serialize(&self, serializer: &Serializer<'_>, out: &mut impl fmt::Write) -> fmt::Result
+    {
+        serializer.write_document(out, self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Serializer<'s>
+{
+    // the text the tree was parsed from, unchanged parts get copied straight from it
+    source: Option<&'s str>
+}
+
+impl<'s> Serializer<'s>
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    // anything that wasnt modified comes out exactly like it was in the source
+    pub fn preserve(mut self, source: &'s str) -> Self
+    {
+        self.source = Some(source);
+
+        self
+    }
+
+    pub fn write(&self, out: &mut impl fmt::Write, node: &impl Serialize) -> fmt::Result
+    {
+        node.serialize(self, out)
+    }
+
+    pub fn write_io(&self, out: impl io::Write, node: &impl Serialize) -> io::Result<()>
+    {
+        let mut writer = IoWriter{inner: out, error: None};
+
+        node.serialize(self, &mut writer).map_err(|_|
+        {
+            writer.error.take().unwrap_or_else(|| io::Error::other("formatting failed"))
+        })
+    }
+
+    pub fn to_string(&self, node: &impl Serialize) -> String
+    {
+        let mut out = String::new();
+        self.write(&mut out, node).expect("writing to a string cant fail");
+
+        out
+    }
+
+    // the original text of a span if theres a source and the span is actually in it
+    fn original(&self, span: Span) -> Option<&'s str>
+    {
+        let source = self.source?;
+
+        (!span.is_empty()).then(|| span.slice(source)).flatten()
+    }
+
+    fn write_document(&self, out: &mut impl fmt::Write, document: &Document<'_>) -> fmt::Result
+    {
+        if let (Some(source), false) = (self.source, document.is_modified())
+        {
+            return out.write_str(source);
+        }
+
+        if let Some(declaration) = document.declaration()
+        {
+            Self::write_declaration(out, declaration)?;
+        }
+
+        if let Some(doctype) = document.doctype()
+        {
+            Self::write_doctype(out, doctype)?;
+        }
+
+        document.children().iter().try_for_each(|child| self.write_child(out, child, None))
+    }
+
+    pub(super) fn write_doctype(out: &mut impl fmt::Write, doctype: &Doctype) -> fmt::Result
+    {
+        out.write_str("<!DOCTYPE")?;
+
+        if let Some(name) = doctype.name()
+        {
+            write!(out, " {name}")?;
+        }
+
+        match (doctype.public_id(), doctype.system_id())
+        {
+            (Some(public_id), Some(system_id)) => write!(out, " PUBLIC \"{public_id}\" \"{system_id}\"")?,
+            (Some(public_id), None) => write!(out, " PUBLIC \"{public_id}\"")?,
+            (None, Some(system_id)) => write!(out, " SYSTEM \"{system_id}\"")?,
+            (None, None) => ()
+        }
+
+        out.write_char('>')
+    }
+
+    fn write_declaration(out: &mut impl fmt::Write, declaration: &XmlDeclaration) -> fmt::Result
+    {
+        write!(out, "<?xml version=\"{}\"", declaration.version())?;
+
+        if let Some(encoding) = declaration.encoding()
+        {
+            write!(out, " encoding=\"{encoding}\"")?;
+        }
+
+        if let Some(standalone) = declaration.standalone()
+        {
+            write!(out, " standalone=\"{}\"", if standalone { "yes" } else { "no" })?;
+        }
+
+        out.write_str("?>")
+    }
+
+    // the parent decides how text gets written, raw text elements in html and escaped like xml in xml
+    fn write_child(&self, out: &mut impl fmt::Write, child: &Child<'_>, parent: Option<&Element<'_>>) -> fmt::Result
+    {
+        match child
+        {
+            Child::Element(element) => self.write_element(out, element),
+            Child::Text(text) => self.write_text(out, text, parent),
+            Child::Comment(comment) => write!(out, "{comment}"),
+            Child::ProcessingInstruction(instruction) => write!(out, "{instruction}")
+        }
+    }
+
+    fn write_element(&self, out: &mut impl fmt::Write, element: &Element<'_>) -> fmt::Result
+    {
+        if !element.is_modified() && Self::is_exact(element)
+        {
+            if let Some(original) = self.original(element.span())
+            {
+                return out.write_str(original);
+            }
+        }
+
+        let original = element.original_name();
+
+        write!(out, "<{original}")?;
+
+        for tag in element.tags()
+        {
+            out.write_char(' ')?;
+            self.write_tag(out, tag, element.is_xml())?;
+        }
+
+        if element.is_xml() && element.children().is_empty()
+        {
+            return out.write_str("/>");
+        }
+
+        out.write_char('>')?;
+
+        if element.is_childless()
+        {
+            return Ok(());
+        }
+
+        element.children().iter().try_for_each(|child| self.write_child(out, child, Some(element)))?;
+
+        write!(out, "</{original}>")
+    }
+
+    // reconstructed elements dont match their spans, and neither do implied ends around them
+    fn is_exact(element: &Element<'_>) -> bool
+    {
+        !element.is_reconstructed && !element.children().iter().any(|child|
+        {
+            child.element().is_some_and(|element| element.is_reconstructed)
+        })
+    }
+
+    // xml has no tags without a value, those get an empty one
+    fn write_tag(&self, out: &mut impl fmt::Write, tag: &Tag<'_>, is_xml: bool) -> fmt::Result
+    {
+        if let Some(original) = self.original(tag.span())
+        {
+            return out.write_str(original);
+        }
+
+        out.write_str(tag.original_name())?;
+
+        match tag.content()
+        {
+            Some(content) =>
+            {
+                out.write_str("=\"")?;
+                escape(out, content, true, is_xml)?;
+                out.write_char('"')
+            },
+            None if is_xml => out.write_str("=\"\""),
+            None => Ok(())
+        }
+    }
+
+    fn write_text(&self, out: &mut impl fmt::Write, text: &Text<'_>, parent: Option<&Element<'_>>) -> fmt::Result
+    {
+        if self.original(text.span()).is_some()
+        {
+            return out.write_str(text.raw());
+        }
+
+        let is_xml = parent.is_some_and(Element::is_xml);
+        let is_raw = parent.is_some_and(|parent| !is_xml && is_raw_text(parent.name()));
+
+        if is_raw
+        {
+            out.write_str(text.content())
+        } else
+        {
+            escape(out, text.content(), false, is_xml)
+        }
+    }
+}
+
+impl<'a> fmt::Display for Comment<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "<!--{}-->", self.text())
+    }
+}
+
+impl<'a> fmt::Display for ProcessingInstruction<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        if self.data().is_empty()
+        {
+            write!(f, "<?{}?>", self.target())
+        } else
+        {
+            write!(f, "<?{} {}?>", self.target(), self.data())
+        }
+    }
+}
+
+impl<'a> fmt::Display for Element<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        Serializer::new().write(f, self)
+    }
+}
+
+impl<'a> fmt::Display for Child<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        Serializer::new().write(f, self)
+    }
+}
+
+impl<'a> fmt::Display for Document<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        Serializer::new().write(f, self)
+    }
+}
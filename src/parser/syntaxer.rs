@@ -1,4 +1,8 @@
-use std::iter::Peekable;
+use std::{
+    mem,
+    borrow::Cow,
+    iter::Peekable
+};
 
 use lexer::{
     Lexeme,
@@ -6,126 +10,189 @@ use lexer::{
     Lexer
 };
 
-pub use lexer::TextIterInner;
+use crate::parser::{
+    Doctype,
+    Namespace,
+    Position,
+    Span,
+    ParseError,
+    ParseErrorKind,
+    Diagnostic,
+    DiagnosticKind
+};
+
+pub use lexer::{
+    Quote,
+    Text,
+    TextIterInner
+};
 
-mod lexer;
+pub mod lexer;
 
 
-fn unexpected_lexeme(lexeme: impl Into<Option<Lexeme>>, expected: &str) -> !
+// the original only needs to be kept if lowercasing changed it
+fn spelling<'a>(name: &str, original: &'a str) -> Option<Cow<'a, str>>
 {
-    let lexeme = lexeme.into();
-    let name = match lexeme
+    (name != original).then_some(Cow::Borrowed(original))
+}
+
+// what a lexeme is in words, for errors and diagnostics
+fn describe(lexeme: Option<&Lexeme<'_>>) -> String
+{
+    let kind = match lexeme
     {
-        Some(ref lexeme) => format!("{lexeme:?}"),
-        None => "none".to_owned()
+        Some(lexeme) => lexeme.kind(),
+        None => return "end of input".to_owned()
     };
 
-    match lexeme
+    match kind
     {
-        Some(lexeme) =>
-        {
-            let line_num = lexeme.line();
-            panic!("line {}: unexpected {}, expected {}", line_num, name, expected)
-        },
-        None =>
-        {
-            panic!("unexpected {}, expected {}", name, expected)
-        }
+        LexemeType::BracketLeft => "'<'".to_owned(),
+        LexemeType::BracketRight => "'>'".to_owned(),
+        LexemeType::Identifier(_, original) => format!("the name {original}"),
+        LexemeType::Text(_) => "text".to_owned(),
+        LexemeType::Equals => "'='".to_owned(),
+        LexemeType::EndSlash => "'/'".to_owned(),
+        LexemeType::Literal(_, Quote::Unquoted) => "an unquoted value".to_owned(),
+        LexemeType::Literal(..) => "a quoted value".to_owned(),
+        LexemeType::Comment(_) => "a comment".to_owned(),
+        LexemeType::Doctype(_) => "a doctype".to_owned()
     }
 }
 
+fn unexpected_lexeme<'a>(lexeme: impl Into<Option<Lexeme<'a>>>, expected: &str) -> ParseError
+{
+    let lexeme = lexeme.into();
+    let found = describe(lexeme.as_ref());
+
+    let line = lexeme.map(|lexeme| lexeme.line());
+
+    ParseError::new(ParseErrorKind::UnexpectedLexeme, expected, found, line)
+}
+
 #[derive(Debug, Clone)]
-pub enum Leaf
+pub enum Leaf<'a>
 {
-    Body(ElementBody),
-    Content(String),
-    End(ElementEnd)
+    Body(ElementBody<'a>),
+    Content(Text<'a>),
+    // the text and where the whole comment is
+    Comment(&'a str, Span),
+    Doctype(Doctype),
+    End(ElementEnd<'a>)
 }
 
-impl Leaf
+impl<'a> Leaf<'a>
 {
-    pub fn parse(mut s_lexemes: impl Iterator<Item=Lexeme>) -> (Option<Self>, Self)
+    pub fn parse(
+        s_lexemes: &mut impl Iterator<Item=Result<Lexeme<'a>, ParseError>>,
+        diagnostics: &mut Vec<Diagnostic>
+    ) -> Result<(Option<Self>, Self), ParseError>
     {
-        match s_lexemes.next()
+        let start = match s_lexemes.next().transpose()?
         {
             Some(x) =>
             {
                 match x.kind()
                 {
-                    LexemeType::BracketLeft => (),
-                    // useless clone but who cares
-                    LexemeType::Identifier(content) =>
+                    LexemeType::BracketLeft => x.span().start,
+                    LexemeType::Text(_) | LexemeType::Comment(_) | LexemeType::Doctype(_) =>
                     {
-                        return (None, Self::Content(content.clone()));
+                        return Ok((None, Self::from_lexeme(x)));
                     },
-                    _ => unexpected_lexeme(x, "BracketLeft or Identifier")
+                    _ => return Err(unexpected_lexeme(x, "a tag, text, a comment or a doctype"))
                 }
             },
-            x => unexpected_lexeme(x, "BracketLeft or Identifier")
-        }
+            x => return Err(unexpected_lexeme(x, "a tag, text, a comment or a doctype"))
+        };
 
+        let mut end = start;
         let mut has_preceding = false;
         let mut is_short_end = false;
-        let mut lexemes = s_lexemes.by_ref().take_while(|lexeme|
+        // a / after the name only closes the tag if a > comes right after it
+        let mut slash = None;
+        let mut lexemes = Vec::new();
+        while let Some(lexeme) = s_lexemes.next().transpose()?
         {
+            end = lexeme.span().end;
+
             match lexeme.kind()
             {
-                LexemeType::BracketRight => false,
-                LexemeType::EndSlash =>
+                LexemeType::BracketRight =>
                 {
-                    if has_preceding
-                    {
-                        is_short_end = true;
+                    is_short_end = slash.is_some();
 
-                        false
-                    } else
+                    break;
+                },
+                LexemeType::EndSlash if has_preceding =>
+                {
+                    if let Some(position) = slash.replace(lexeme.position())
                     {
-                        true
+                        diagnostics.push(Diagnostic::new(DiagnosticKind::StrayToken, "'/'", position));
                     }
+
+                    continue;
                 },
-                _ =>
-                {
-                    has_preceding = true;
-                    true
-                }
+                LexemeType::EndSlash => (),
+                _ => has_preceding = true
             }
-        });
 
-        let leaf = Self::parse_leaf(&mut lexemes);
+            // its just whitespace then
+            if let Some(position) = slash.take()
+            {
+                diagnostics.push(Diagnostic::new(DiagnosticKind::StrayToken, "'/'", position));
+            }
 
-        let optional_leaf = is_short_end.then(||
-        {
-            let lexeme = s_lexemes.next();
+            lexemes.push(lexeme);
+        }
+
+        let leaf = Self::parse_leaf(lexemes.into_iter(), Span::new(start, end), diagnostics)?;
 
-            match lexeme.as_ref().map(|l| l.kind())
+        let optional_leaf = if is_short_end
+        {
+            let (name, position) = match &leaf
             {
-                Some(LexemeType::BracketRight) =>
+                Self::Body(x) =>
                 {
-                    let (name, line) = match &leaf
-                    {
-                        Self::Body(x) =>
-                        {
-                            (x.name.clone(), x.line)
-                        },
-                        Self::End(x) =>
-                        {
-                            (x.name.clone(), x.line)
-                        },
-                        Self::Content(_) => unreachable!()
-                    };
-
-                    let end = ElementEnd::new(name, line);
-
-                    Self::End(end)
+                    (x.name.clone(), x.position)
                 },
-                _ => unexpected_lexeme(lexeme, "BracketRight")
-            }
-        });
+                Self::End(x) =>
+                {
+                    (x.name.clone(), x.position)
+                },
+                Self::Content(_) | Self::Comment(..) | Self::Doctype(_) => unreachable!()
+            };
+
+            // theres no actual end tag so it takes up no space right after the />
+            let end = ElementEnd{is_self_closing: true, ..ElementEnd::new(name, position, Span::new(end, end))};
+
+            Some(Self::End(end))
+        } else
+        {
+            None
+        };
+
+        Ok((optional_leaf, leaf))
+    }
+
+    // the leaves that r just one lexeme
+    fn from_lexeme(lexeme: Lexeme<'a>) -> Self
+    {
+        let span = lexeme.span();
 
-        (optional_leaf, leaf)
+        match lexeme.into_kind()
+        {
+            LexemeType::Text(text) => Self::Content(text),
+            LexemeType::Comment(content) => Self::Comment(content, span),
+            LexemeType::Doctype(content) => Self::Doctype(Doctype::parse(content)),
+            _ => unreachable!()
+        }
     }
 
-    fn parse_leaf(mut lexemes: impl Iterator<Item=Lexeme>) -> Self
+    fn parse_leaf(
+        mut lexemes: impl Iterator<Item=Lexeme<'a>>,
+        span: Span,
+        diagnostics: &mut Vec<Diagnostic>
+    ) -> Result<Self, ParseError>
     {
         match lexemes.next()
         {
@@ -133,24 +200,34 @@ impl Leaf
             {
                 match x.kind()
                 {
-                    LexemeType::Identifier(name) =>
+                    LexemeType::Identifier(name, original) =>
                     {
-                        let body = ElementBody::parse(name.clone(), x.line(), &mut lexemes);
-
-                        Self::Body(body)
+                        let body = ElementBody::parse(
+                            name.clone(),
+                            original,
+                            x.position(),
+                            span,
+                            &mut lexemes,
+                            diagnostics
+                        )?;
+
+                        Ok(Self::Body(body))
                     },
                     LexemeType::EndSlash =>
                     {
-                        Self::parse_ending(lexemes)
+                        Self::parse_ending(lexemes, span)
                     },
-                    _ => unexpected_lexeme(x, "Identifier or EndSlash")
+                    _ => Err(unexpected_lexeme(x, "a name or '/'"))
                 }
             }
-            x => unexpected_lexeme(x, "Identifier or EndSlash")
+            x => Err(unexpected_lexeme(x, "a name or '/'"))
         }
     }
 
-    fn parse_ending(mut lexemes: impl Iterator<Item=Lexeme>) -> Self
+    fn parse_ending(
+        mut lexemes: impl Iterator<Item=Lexeme<'a>>,
+        span: Span
+    ) -> Result<Self, ParseError>
     {
         match lexemes.next()
         {
@@ -158,67 +235,129 @@ impl Leaf
             {
                 match lexeme.kind()
                 {
-                    LexemeType::Identifier(name) =>
+                    LexemeType::Identifier(name, _) =>
                     {
-                        let end = ElementEnd::new(name.clone(), lexeme.line());
+                        let end = ElementEnd::new(name.clone(), lexeme.position(), span);
 
                         // consume all the lexemes
                         lexemes.for_each(drop);
 
-                        Self::End(end)
+                        Ok(Self::End(end))
                     },
-                    _ => unexpected_lexeme(lexeme, "Identifier")
+                    _ => Err(unexpected_lexeme(lexeme, "a name"))
                 }
             },
-            x => unexpected_lexeme(x, "Identifier")
+            x => Err(unexpected_lexeme(x, "a name"))
         }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct Tag
+pub struct Tag<'a>
 {
-    name: String,
-    content: Option<String>
+    name: Cow<'a, str>,
+    // how the name was written in the text if thats different
+    spelling: Option<Cow<'a, str>>,
+    // what the prefix was bound to, only xml resolves prefixes like that
+    namespace_uri: Option<Cow<'a, str>>,
+    position: Position,
+    span: Span,
+    content: Option<Text<'a>>,
+    quote: Option<Quote>
 }
 
-impl Tag
+impl<'a> Tag<'a>
 {
-    pub fn parse(mut lexemes: impl Iterator<Item=Lexeme>) -> Self
+    // a tag with no value, like disabled or hidden
+    pub fn new(name: impl Into<Cow<'a, str>>) -> Self
+    {
+        Self{
+            name: name.into(),
+            spelling: None,
+            namespace_uri: None,
+            position: Position::default(),
+            span: Span::default(),
+            content: None,
+            quote: None
+        }
+    }
+
+    // the value is used as is, entities in it dont get decoded
+    pub fn with_content(name: impl Into<Cow<'a, str>>, content: impl Into<Cow<'a, str>>) -> Self
+    {
+        Self{
+            content: Some(Text::new(content)),
+            quote: Some(Quote::Double),
+            ..Self::new(name)
+        }
+    }
+
+    // a tag that was parsed somewhere other than the lexer, like in xml
+    pub(super) fn from_parts(name: impl Into<Cow<'a, str>>, span: Span, content: Text<'a>, quote: Quote) -> Self
     {
-        let name = match lexemes.next()
+        Self{
+            name: name.into(),
+            spelling: None,
+            namespace_uri: None,
+            position: span.start,
+            span,
+            content: Some(content),
+            quote: Some(quote)
+        }
+    }
+
+    pub fn parse(
+        lexemes: &mut Peekable<impl Iterator<Item=Lexeme<'a>>>,
+        diagnostics: &mut Vec<Diagnostic>
+    ) -> Result<Self, ParseError>
+    {
+        let (name, spelling, span) = match lexemes.next()
         {
             Some(x) =>
             {
                 match x.kind()
                 {
-                    LexemeType::Identifier(name) => name.clone(),
-                    _ => unexpected_lexeme(x, "Identifier")
+                    LexemeType::Identifier(name, original) =>
+                    {
+                        (name.clone(), spelling(name, original), x.span())
+                    },
+                    _ => return Err(unexpected_lexeme(x, "a name"))
                 }
             }
-            x => unexpected_lexeme(x, "Identifier")
+            x => return Err(unexpected_lexeme(x, "a name"))
         };
 
-        let content = if let Some(LexemeType::Equals) = lexemes.next().map(|l| l.kind().clone())
+        let value = match lexemes.next_if(|l| *l.kind() == LexemeType::Equals)
         {
-            match lexemes.next()
+            Some(equals) =>
             {
-                Some(x) =>
+                let literal = lexemes.next_if(|l| matches!(l.kind(), LexemeType::Literal(..)));
+
+                match literal.map(Lexeme::into_kind)
                 {
-                    match x.kind()
+                    Some(LexemeType::Literal(content, quote)) => Some((content, quote)),
+                    // like href= with nothing after it, the value is empty
+                    _ =>
                     {
-                        LexemeType::Literal(content) => Some(content.clone()),
-                        _ => unexpected_lexeme(x, "Literal")
+                        diagnostics.push(Diagnostic::new(DiagnosticKind::MissingValue, name.as_ref(), span.start));
+
+                        let position = equals.span().end;
+                        let content = Text::from_parts("", None, Span::new(position, position));
+
+                        Some((content, Quote::Unquoted))
                     }
-                },
-                x => unexpected_lexeme(x, "Literal")
-            }
-        } else
-        {
-            None
+                }
+            },
+            None => None
         };
 
-        Self{name, content}
+        let (content, quote) = value.unzip();
+
+        let position = span.start;
+        let end = content.as_ref().map(|content| content.span().end).unwrap_or(span.end);
+        let span = Span::new(position, end);
+
+        Ok(Self{name, spelling, namespace_uri: None, position, span, content, quote})
     }
 
     pub fn name(&self) -> &str
@@ -226,87 +365,288 @@ impl Tag
         &self.name
     }
 
-    pub fn content(&self) -> &Option<String>
+    // the name like it was written, the same as name if it wasnt in the text
+    pub fn original_name(&self) -> &str
+    {
+        self.spelling.as_deref().unwrap_or(&self.name)
+    }
+
+    // foreign content wants some names in camel case, the spelling from the text stays
+    pub(super) fn adjust_name(&mut self, name: &'static str)
+    {
+        let old = mem::replace(&mut self.name, Cow::Borrowed(name));
+
+        self.spelling.get_or_insert(old);
+    }
+
+    // the part before the colon in names like xlink:href
+    pub fn prefix(&self) -> Option<&str>
+    {
+        self.name.split_once(':').map(|(prefix, _)| prefix)
+    }
+
+    pub fn local_name(&self) -> &str
+    {
+        self.name.split_once(':').map(|(_, local)| local).unwrap_or(&self.name)
+    }
+
+    // only the xlink, xml and xmlns prefixes mean anything in html, other tags r in no namespace
+    pub fn namespace(&self) -> Option<Namespace>
+    {
+        if let Some(uri) = &self.namespace_uri
+        {
+            return Some(Namespace::from_uri(uri).unwrap_or(Namespace::Other));
+        }
+
+        if self.name == "xmlns"
+        {
+            return Some(Namespace::Xmlns);
+        }
+
+        self.prefix().and_then(Namespace::from_prefix)
+    }
+
+    // for namespaces that arent one of the known ones, like the ones in rss feeds
+    pub fn namespace_uri(&self) -> Option<&str>
+    {
+        self.namespace_uri.as_deref().or_else(|| self.namespace().map(|namespace| namespace.uri()))
+    }
+
+    pub(super) fn set_namespace_uri(&mut self, uri: Cow<'a, str>)
+    {
+        self.namespace_uri = Some(uri);
+    }
+
+    pub fn position(&self) -> Position
+    {
+        self.position
+    }
+
+    // from the name up to the end of the value
+    pub fn span(&self) -> Span
+    {
+        self.span
+    }
+
+    pub fn content_span(&self) -> Option<Span>
+    {
+        self.content.as_ref().map(|content| content.span())
+    }
+
+    pub fn content(&self) -> Option<&str>
+    {
+        self.content.as_ref().map(|content| content.content())
+    }
+
+    pub fn raw_content(&self) -> Option<&str>
     {
-        &self.content
+        self.content.as_ref().map(|content| content.raw())
+    }
+
+    pub fn quote(&self) -> Option<Quote>
+    {
+        self.quote
+    }
+
+    pub fn into_owned(self) -> Tag<'static>
+    {
+        Tag{
+            name: Cow::Owned(self.name.into_owned()),
+            spelling: self.spelling.map(|spelling| Cow::Owned(spelling.into_owned())),
+            namespace_uri: self.namespace_uri.map(|uri| Cow::Owned(uri.into_owned())),
+            position: self.position,
+            span: self.span,
+            content: self.content.map(Text::into_owned),
+            quote: self.quote
+        }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct ElementBody
+pub struct ElementBody<'a>
 {
-    pub name: String,
-    pub line: u64,
-    pub tags: Box<[Tag]>
+    pub name: Cow<'a, str>,
+    pub position: Position,
+    // from < to >
+    pub span: Span,
+    pub tags: Box<[Tag<'a>]>,
+    // how the name was written in the text if thats different
+    pub spelling: Option<Cow<'a, str>>
 }
 
-impl ElementBody
+impl<'a> ElementBody<'a>
 {
-    pub fn parse(name: String, line: u64, lexemes: impl Iterator<Item=Lexeme>) -> Self
+    pub fn parse(
+        name: Cow<'a, str>,
+        original: &'a str,
+        position: Position,
+        span: Span,
+        lexemes: impl Iterator<Item=Lexeme<'a>>,
+        diagnostics: &mut Vec<Diagnostic>
+    ) -> Result<Self, ParseError>
     {
         let mut tags = Vec::new();
 
         let mut lexemes = lexemes.peekable();
-        while let Some(_) = lexemes.peek()
+        while let Some(lexeme) = lexemes.peek()
         {
-            let tag = Tag::parse(&mut lexemes);
+            // stuff like <div =x> or <a "x"> where theres no name, it gets dropped
+            if !matches!(lexeme.kind(), LexemeType::Identifier(..))
+            {
+                let lexeme = lexemes.next().unwrap();
+                diagnostics.push(Diagnostic::new(DiagnosticKind::StrayToken, describe(Some(&lexeme)), lexeme.position()));
+
+                continue;
+            }
+
+            let tag = Tag::parse(&mut lexemes, diagnostics)?;
 
             tags.push(tag);
         }
 
         let tags = tags.into_boxed_slice();
 
-        Self{name, line, tags}
+        let spelling = spelling(&name, original);
+
+        Ok(Self{name, position, span, tags, spelling})
+    }
+
+    pub fn original_name(&self) -> &str
+    {
+        self.spelling.as_deref().unwrap_or(&self.name)
+    }
+
+    pub(super) fn adjust_name(&mut self, name: &'static str)
+    {
+        let old = mem::replace(&mut self.name, Cow::Borrowed(name));
+
+        self.spelling.get_or_insert(old);
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct ElementEnd
+pub struct ElementEnd<'a>
 {
-    pub name: String,
-    pub line: u64
+    pub name: Cow<'a, str>,
+    pub position: Position,
+    pub span: Span,
+    // made up from the /> of a self closing start tag
+    pub is_self_closing: bool
 }
 
-impl ElementEnd
+impl<'a> ElementEnd<'a>
 {
-    pub fn new(name: String, line: u64) -> Self
+    pub fn new(name: impl Into<Cow<'a, str>>, position: Position, span: Span) -> Self
     {
-        Self{name, line}
+        Self{name: name.into(), position, span, is_self_closing: false}
     }
 }
 
 pub struct Syntaxer<'a>
 {
-    cached_leaf: Option<Leaf>,
-    lexer: Peekable<Lexer<'a>>
+    cached_leaf: Option<Leaf<'a>>,
+    peeked: Option<Option<Result<Leaf<'a>, ParseError>>>,
+    skip_comments: bool,
+    diagnostics: Vec<Diagnostic>,
+    lexer: Lexer<'a>
 }
 
 impl<'a> Syntaxer<'a>
 {
     pub fn new(text: TextIterInner<'a>) -> Self
     {
-        let lexer = Lexer::new(text).peekable();
+        let lexer = Lexer::new(text);
+
+        Self{
+            cached_leaf: None,
+            peeked: None,
+            skip_comments: false,
+            diagnostics: Vec::new(),
+            lexer
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&Result<Leaf<'a>, ParseError>>
+    {
+        if self.peeked.is_none()
+        {
+            self.peeked = Some(self.next_unpeeked());
+        }
 
-        Self{cached_leaf: None, lexer}
+        self.peeked.as_ref().unwrap().as_ref()
     }
-}
 
-impl<'a> Iterator for Syntaxer<'a>
-{
-    type Item = Leaf;
+    // everything the lexer and syntaxer recovered from so far
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic>
+    {
+        let mut diagnostics = self.lexer.take_diagnostics();
+        diagnostics.append(&mut self.diagnostics);
 
-    fn next(&mut self) -> Option<Self::Item>
+        diagnostics
+    }
+
+    fn check_duplicates(&mut self, body: &ElementBody<'a>)
+    {
+        for (index, tag) in body.tags.iter().enumerate()
+        {
+            if body.tags[..index].iter().any(|other| other.name.eq_ignore_ascii_case(&tag.name))
+            {
+                let diagnostic = Diagnostic::new(
+                    DiagnosticKind::DuplicateAttribute,
+                    tag.name.as_ref(),
+                    tag.position
+                );
+
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    pub fn skip_comments(&mut self, skip: bool)
+    {
+        self.skip_comments = skip;
+    }
+
+    pub fn decode_entities(&mut self, decode: bool)
+    {
+        self.lexer.decode_entities(decode);
+    }
+
+    fn next_unpeeked(&mut self) -> Option<Result<Leaf<'a>, ParseError>>
+    {
+        loop
+        {
+            let leaf = self.next_leaf();
+
+            if self.skip_comments && matches!(leaf, Some(Ok(Leaf::Comment(..))))
+            {
+                continue;
+            }
+
+            return leaf;
+        }
+    }
+
+    fn next_leaf(&mut self) -> Option<Result<Leaf<'a>, ParseError>>
     {
         if let Some(leaf) = self.cached_leaf.take()
         {
-            return Some(leaf);
+            return Some(Ok(leaf));
         }
 
-        if self.lexer.peek().is_some()
+        if !self.lexer.is_finished()
         {
-            let (optional_leaf, leaf) = Leaf::parse(&mut self.lexer);
+            let leaf = Leaf::parse(&mut self.lexer, &mut self.diagnostics).map(|(optional_leaf, leaf)|
+            {
+                self.cached_leaf = optional_leaf;
 
-            self.cached_leaf = optional_leaf;
+                leaf
+            });
+
+            if let Ok(Leaf::Body(body)) = &leaf
+            {
+                self.check_duplicates(body);
+            }
 
             Some(leaf)
         } else
@@ -315,3 +655,17 @@ impl<'a> Iterator for Syntaxer<'a>
         }
     }
 }
+
+impl<'a> Iterator for Syntaxer<'a>
+{
+    type Item = Result<Leaf<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        match self.peeked.take()
+        {
+            Some(leaf) => leaf,
+            None => self.next_unpeeked()
+        }
+    }
+}
@@ -1,134 +1,535 @@
 use std::{
+    mem,
+    borrow::Cow,
+    ops::Range,
     str::Chars,
     iter::Peekable
 };
 
+use crate::parser::{
+    Position,
+    Span,
+    ParseError,
+    ParseErrorKind,
+    Diagnostic,
+    DiagnosticKind
+};
+
+mod entities;
+
+
+const COMMENT_START: &str = "<!--";
+const COMMENT_END: &str = "-->";
+const DOCTYPE_START: &str = "<!doctype";
+
+const RAW_TEXT_ELEMENTS: [&str; 6] = [
+    "script",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes"
+];
 
-enum Action
+const ESCAPABLE_RAW_TEXT_ELEMENTS: [&str; 2] = [
+    "title",
+    "textarea"
+];
+
+// elements whose text is taken as it is, the serializer writes it back out the same way
+pub(crate) fn is_raw_text(name: &str) -> bool
 {
-    ReturnLexemeType(LexemeType),
+    TextMode::from_name(name) == Some(TextMode::Raw)
+}
+
+// html names dont care about case so theyre lowercased right away,
+// most r already lowercase and stay borrowed
+fn lowercase_name(name: &str) -> Cow<'_, str>
+{
+    if name.bytes().any(|c| c.is_ascii_uppercase())
+    {
+        Cow::Owned(name.to_ascii_lowercase())
+    } else
+    {
+        Cow::Borrowed(name)
+    }
+}
+
+enum Action<'a>
+{
+    ReturnLexemeType(LexemeType<'a>),
+    Comment,
+    Doctype,
+    BogusComment,
     StopConsume,
     Stop,
     Skip,
     Continue
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextMode
+{
+    // script and style, everything until the end tag is text
+    Raw,
+    // title and textarea, same thing but character references still work
+    EscapableRaw
+}
+
+impl TextMode
+{
+    fn from_name(name: &str) -> Option<Self>
+    {
+        let is_name = |x: &&str| x.eq_ignore_ascii_case(name);
+
+        if RAW_TEXT_ELEMENTS.iter().any(is_name)
+        {
+            Some(Self::Raw)
+        } else if ESCAPABLE_RAW_TEXT_ELEMENTS.iter().any(is_name)
+        {
+            Some(Self::EscapableRaw)
+        } else
+        {
+            None
+        }
+    }
+}
+
 #[derive(Debug)]
-struct State
+struct State<'a>
 {
-    line: u64,
+    position: Position,
+    diagnostics: Vec<Diagnostic>,
+    decode_entities: bool,
     is_content: bool,
-    is_literal: bool
+    is_value_start: bool,
+    is_tag_start: bool,
+    // the element whose start tag is being lexed, if its contents r raw text
+    raw_element: Option<(&'a str, TextMode)>,
+    // the element whose raw text comes next
+    raw_text: Option<(&'a str, TextMode)>
 }
 
-impl Default for State
+impl<'a> Default for State<'a>
 {
     fn default() -> Self
     {
         Self{
-            line: 1,
-            is_content: false,
-            is_literal: false
+            position: Position::default(),
+            diagnostics: Vec::new(),
+            decode_entities: true,
+            is_content: true,
+            is_value_start: false,
+            is_tag_start: false,
+            raw_element: None,
+            raw_text: None
+        }
+    }
+}
+
+impl<'a> State<'a>
+{
+    fn update(&mut self, kind: &LexemeType<'a>)
+    {
+        self.is_value_start = *kind == LexemeType::Equals;
+
+        match kind
+        {
+            LexemeType::BracketLeft =>
+            {
+                self.is_tag_start = true;
+                self.raw_element = None;
+
+                return;
+            },
+            LexemeType::Identifier(_, original) if self.is_tag_start =>
+            {
+                self.raw_element = TextMode::from_name(original).map(|mode| (*original, mode));
+            },
+            LexemeType::BracketRight =>
+            {
+                self.raw_text = self.raw_element.take();
+            },
+            _ => ()
         }
+
+        self.is_tag_start = false;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quote
+{
+    Double,
+    Single,
+    Unquoted
+}
+
+impl Quote
+{
+    pub fn delimiter(&self) -> Option<char>
+    {
+        match self
+        {
+            Self::Double => Some('"'),
+            Self::Single => Some('\''),
+            Self::Unquoted => None
+        }
+    }
+}
+
+// borrowed straight from the text unless it was decoded from bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text<'a>
+{
+    raw: Cow<'a, str>,
+    // only there if decoding changed anything
+    decoded: Option<String>,
+    span: Span
+}
+
+impl<'a> Text<'a>
+{
+    pub fn new(raw: impl Into<Cow<'a, str>>) -> Self
+    {
+        Self{raw: raw.into(), decoded: None, span: Span::default()}
+    }
+
+    pub fn decode(
+        raw: impl Into<Cow<'a, str>>,
+        is_attribute: bool,
+        on_unknown: impl FnMut(&str, usize)
+    ) -> Self
+    {
+        let raw = raw.into();
+        let decoded = entities::decode(&raw, is_attribute, on_unknown);
+
+        Self{raw, decoded, span: Span::default()}
+    }
+
+    // for text that didnt come from the lexer, like xml
+    pub(crate) fn from_parts(raw: impl Into<Cow<'a, str>>, decoded: Option<String>, span: Span) -> Self
+    {
+        Self{raw: raw.into(), decoded, span}
+    }
+
+    pub fn into_owned(self) -> Text<'static>
+    {
+        Text{raw: Cow::Owned(self.raw.into_owned()), decoded: self.decoded, span: self.span}
+    }
+
+    pub fn is_borrowed(&self) -> bool
+    {
+        matches!(self.raw, Cow::Borrowed(_)) && self.decoded.is_none()
+    }
+
+    pub fn raw(&self) -> &str
+    {
+        &self.raw
+    }
+
+    pub fn content(&self) -> &str
+    {
+        self.decoded.as_deref().unwrap_or(&self.raw)
+    }
+
+    // includes the quotes for attribute values
+    pub fn span(&self) -> Span
+    {
+        self.span
     }
 }
 
 struct LexemeParser<'a, 'b>
 {
-    state: &'b mut State,
-    collected: String,
+    state: &'b mut State<'a>,
+    source: &'a str,
+    start: Position,
+    end: Position,
+    // where the collected characters r in the source
+    collected: Range<usize>,
+    is_text: bool,
+    literal: Option<Quote>,
+    is_literal_closed: bool,
     text: &'b mut TextIter<'a>
 }
 
 impl<'a, 'b> LexemeParser<'a, 'b>
 {
-    fn new(state: &'b mut State, text: &'b mut TextIter<'a>) -> Self
+    fn new(state: &'b mut State<'a>, source: &'a str, text: &'b mut TextIter<'a>) -> Self
     {
-        let collected = String::new();
+        let start = state.position;
+        let is_text = state.is_content;
 
-        Self{state, collected, text}
+        Self{
+            state,
+            source,
+            start,
+            end: start,
+            collected: 0..0,
+            is_text,
+            literal: None,
+            is_literal_closed: false,
+            text
+        }
     }
 
-    fn parse(mut self) -> LexemeType
+    fn advance(&mut self) -> Option<char>
     {
-        loop
+        let c = self.text.next()?;
+
+        self.state.position.advance(c);
+
+        Some(c)
+    }
+
+    fn diagnose(&mut self, kind: DiagnosticKind, name: impl Into<String>, position: Position)
+    {
+        self.state.diagnostics.push(Diagnostic::new(kind, name, position));
+    }
+
+    fn collected(&self) -> &'a str
+    {
+        &self.source[self.collected.clone()]
+    }
+
+    // everything from offset up to where the lexer is now
+    fn slice_from(&self, offset: usize) -> &'a str
+    {
+        &self.source[offset..self.state.position.offset]
+    }
+
+    fn text(&mut self, raw: &'a str, is_attribute: bool) -> Text<'a>
+    {
+        if !self.state.decode_entities
+        {
+            return Text::new(raw);
+        }
+
+        // quoted literals start after the quote
+        let mut start = self.start;
+        if let Some(quote) = self.literal.and_then(|quote| quote.delimiter())
         {
-            match self.text.peek()
+            start.advance(quote);
+        }
+
+        let mut unknown = Vec::new();
+        let text = Text::decode(raw, is_attribute, |name, index|
+        {
+            unknown.push((name.to_owned(), index));
+        });
+
+        for (name, index) in unknown
+        {
+            let position = start.advanced(&raw[..index]);
+
+            self.diagnose(DiagnosticKind::UnknownEntity, name, position);
+        }
+
+        text
+    }
+
+    fn span(&self) -> Span
+    {
+        Span::new(self.start, self.end)
+    }
+
+    // the span ends wherever the lexer is now
+    fn finished(&mut self, lexeme: LexemeType<'a>) -> (Span, LexemeType<'a>)
+    {
+        self.end = self.state.position;
+
+        (self.span(), lexeme)
+    }
+
+    fn parse(mut self) -> Result<(Span, LexemeType<'a>), ParseError>
+    {
+        if let Some((name, mode)) = self.state.raw_text.take()
+        {
+            if let Some(lexeme) = self.parse_raw_text(name, mode)
+            {
+                return Ok(self.finished(lexeme));
+            }
+        }
+
+        let mut is_started = false;
+        while let Some(&c) = self.text.peek()
+        {
+            let position = self.state.position;
+            let action = self.parse_char(c);
+
+            // literals start at their opening quote
+            if !is_started && (!matches!(action, Action::Skip) || self.literal.is_some())
             {
-                Some(&c) =>
+                is_started = true;
+                self.start = position;
+                self.end = position;
+            }
+
+            match action
+            {
+                Action::ReturnLexemeType(lexeme) =>
                 {
-                    let action = self.parse_char(c);
+                    self.advance();
 
-                    match action
+                    if !self.collected.is_empty()
                     {
-                        Action::ReturnLexemeType(lexeme) =>
-                        {
-                            self.text.next();
-
-                            if !self.collected.is_empty()
-                            {
-                                panic!("unparsed text: {}", self.collected);
-                            }
-
-                            return lexeme;
-                        },
-                        Action::StopConsume =>
-                        {
-                            self.text.next();
-                            break;
-                        },
-                        Action::Stop => break,
-                        Action::Skip =>
-                        {
-                            self.text.next();
-                            continue;
-                        },
-                        Action::Continue => ()
+                        return Err(ParseError::new(
+                            ParseErrorKind::UnparsedText,
+                            format!("{lexeme:?}"),
+                            self.collected(),
+                            Some(self.start.line)
+                        ));
                     }
 
-                    self.text.next();
-                    self.collected.push(c);
+                    return Ok(self.finished(lexeme));
+                },
+                Action::Comment =>
+                {
+                    let lexeme = self.parse_comment();
+
+                    return Ok(self.finished(lexeme));
                 },
-                None => break
+                Action::Doctype =>
+                {
+                    let lexeme = self.parse_doctype();
+
+                    return Ok(self.finished(lexeme));
+                },
+                Action::BogusComment =>
+                {
+                    let lexeme = self.parse_bogus_comment();
+
+                    return Ok(self.finished(lexeme));
+                },
+                Action::StopConsume =>
+                {
+                    self.advance();
+
+                    // the closing quote is a part of the literal, whitespace isnt
+                    if self.is_literal_closed
+                    {
+                        self.end = self.state.position;
+                    }
+
+                    break;
+                },
+                Action::Stop => break,
+                Action::Skip =>
+                {
+                    self.advance();
+                    continue;
+                },
+                Action::Continue => ()
             }
+
+            self.advance();
+
+            // collected characters always follow each other
+            if self.collected.is_empty()
+            {
+                self.collected.start = position.offset;
+            }
+
+            self.collected.end = self.state.position.offset;
+
+            self.end = self.state.position;
         }
 
-        self.parse_content()
+        let delimiter = self.literal.and_then(|quote| quote.delimiter());
+        if let (Some(delimiter), false) = (delimiter, self.is_literal_closed)
+        {
+            let position = self.start;
+            self.diagnose(DiagnosticKind::UnterminatedLiteral, delimiter, position);
+        }
+
+        let span = self.span();
+
+        Ok((span, self.parse_content()))
     }
 
-    fn parse_char(&mut self, c: char) -> Action
+    fn parse_char(&mut self, c: char) -> Action<'a>
     {
-        if c == '\n'
+        match self.literal
         {
-            self.state.line += 1;
+            Some(Quote::Unquoted) =>
+            {
+                return if c.is_whitespace()
+                {
+                    Action::StopConsume
+                } else if c == '>'
+                {
+                    Action::Stop
+                } else
+                {
+                    Action::Continue
+                };
+            },
+            Some(quote) =>
+            {
+                return if Some(c) == quote.delimiter()
+                {
+                    self.is_literal_closed = true;
+
+                    Action::StopConsume
+                } else
+                {
+                    Action::Continue
+                };
+            },
+            None => ()
         }
 
-        if self.state.is_literal
-        {
-            if c == '"'
-            {
-                self.state.is_literal = false;
+        let is_unquoted_start = self.state.is_value_start
+            && self.collected.is_empty()
+            && !c.is_whitespace()
+            && !['"', '\'', '>'].contains(&c);
 
-                return Action::StopConsume;
-            }
+        if is_unquoted_start
+        {
+            self.literal = Some(Quote::Unquoted);
 
             return Action::Continue;
         }
 
         if self.state.is_content
         {
-            if c == '<'
+            if c != '<'
             {
-                self.state.is_content = false;
+                return Action::Continue;
+            }
 
+            // a </> closes nothing and is dropped
+            if self.starts_with("</>")
+            {
                 if !self.collected.is_empty()
                 {
                     return Action::Stop;
                 }
-            } else
+
+                let position = self.state.position;
+                self.diagnose(DiagnosticKind::StrayBracket, "</>", position);
+
+                self.skip("</");
+
+                return Action::Skip;
+            }
+
+            // a < that cant start a tag is just text like in browsers
+            if !self.is_tag_open()
             {
+                let position = self.state.position;
+                self.diagnose(DiagnosticKind::StrayBracket, "<", position);
+
                 return Action::Continue;
             }
+
+            self.state.is_content = false;
+
+            if !self.collected.is_empty()
+            {
+                return Action::Stop;
+            }
         }
 
         match c
@@ -137,6 +538,25 @@ impl<'a, 'b> LexemeParser<'a, 'b>
             {
                 if self.collected.is_empty()
                 {
+                    if self.starts_with(COMMENT_START)
+                    {
+                        return Action::Comment;
+                    }
+
+                    if self.starts_with(DOCTYPE_START)
+                    {
+                        return Action::Doctype;
+                    }
+
+                    // </ and something thats not a name is a comment too
+                    let is_bogus_end = self.starts_with("</")
+                        && !self.peek_nth(2).is_some_and(|c| c.is_ascii_alphabetic());
+
+                    if self.starts_with("<!") || self.starts_with("<?") || is_bogus_end
+                    {
+                        return Action::BogusComment;
+                    }
+
                     return Action::ReturnLexemeType(LexemeType::BracketLeft);
                 } else
                 {
@@ -175,11 +595,15 @@ impl<'a, 'b> LexemeParser<'a, 'b>
                     Action::Stop
                 };
             },
-            '"' =>
+            '"' | '\'' if self.collected.is_empty() =>
             {
-                self.state.is_literal = true;
+                self.literal = Some(match c
+                {
+                    '"' => Quote::Double,
+                    _ => Quote::Single
+                });
 
-                return Action::Continue;
+                return Action::Skip;
             },
             c if c.is_whitespace() =>
             {
@@ -197,58 +621,209 @@ impl<'a, 'b> LexemeParser<'a, 'b>
         Action::Continue
     }
 
-    fn parse_content(self) -> LexemeType
+    fn starts_with(&self, s: &str) -> bool
+    {
+        let mut text = self.text.clone();
+
+        s.chars().all(|c| text.next().is_some_and(|x| x.eq_ignore_ascii_case(&c)))
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char>
+    {
+        self.text.clone().nth(n)
+    }
+
+    // if a < in text starts a tag, a comment or a doctype
+    fn is_tag_open(&self) -> bool
+    {
+        match self.peek_nth(1)
+        {
+            Some('/') => self.peek_nth(2).is_some(),
+            Some(c) => c.is_ascii_alphabetic() || c == '!' || c == '?',
+            None => false
+        }
+    }
+
+    fn skip(&mut self, s: &str)
     {
-        if self.collected.chars().next() == Some('"')
+        s.chars().for_each(|_| { self.advance(); });
+    }
+
+    // collects everything up to the terminator and consumes it
+    fn collect_until(&mut self, terminator: &str) -> &'a str
+    {
+        let start = self.state.position.offset;
+        while !self.slice_from(start).ends_with(terminator)
         {
-            let literal = self.collected.chars().skip(1).collect::<String>();
-            LexemeType::Literal(literal)
+            // unterminated stuff runs until the end of the text
+            if self.advance().is_none()
+            {
+                return self.slice_from(start);
+            }
+        }
+
+        let content = self.slice_from(start);
+
+        self.state.is_content = true;
+
+        &content[..content.len() - terminator.len()]
+    }
+
+    fn is_raw_text_end(&self, name: &str) -> bool
+    {
+        if !self.starts_with("</") || !self.starts_with(&format!("</{name}"))
+        {
+            return false;
+        }
+
+        let mut text = self.text.clone();
+        text.nth(name.chars().count() + 1);
+
+        match text.next()
+        {
+            Some(c) => c.is_whitespace() || c == '/' || c == '>',
+            None => true
+        }
+    }
+
+    fn parse_raw_text(&mut self, name: &str, mode: TextMode) -> Option<LexemeType<'a>>
+    {
+        let start = self.state.position.offset;
+        while !self.is_raw_text_end(name)
+        {
+            if self.advance().is_none()
+            {
+                break;
+            }
+        }
+
+        let content = self.slice_from(start);
+
+        if content.is_empty()
+        {
+            return None;
+        }
+
+        Some(match mode
+        {
+            TextMode::Raw => LexemeType::Text(Text::new(content)),
+            TextMode::EscapableRaw => LexemeType::Text(self.text(content, false))
+        })
+    }
+
+    fn parse_comment(&mut self) -> LexemeType<'a>
+    {
+        self.skip(COMMENT_START);
+
+        LexemeType::Comment(self.collect_until(COMMENT_END))
+    }
+
+    fn parse_doctype(&mut self) -> LexemeType<'a>
+    {
+        self.skip(DOCTYPE_START);
+
+        LexemeType::Doctype(self.collect_until(">"))
+    }
+
+    // <!whatever>, <?whatever> and </ whatever> r just comments in html
+    fn parse_bogus_comment(&mut self) -> LexemeType<'a>
+    {
+        if self.starts_with("<!")
+        {
+            self.skip("<!");
+        } else if self.starts_with("</")
+        {
+            self.skip("</");
         } else
         {
-            LexemeType::Identifier(self.collected)
+            self.skip("<");
+        }
+
+        LexemeType::Comment(self.collect_until(">"))
+    }
+
+    fn parse_content(mut self) -> LexemeType<'a>
+    {
+        let collected = self.collected();
+
+        match self.literal
+        {
+            Some(quote) => LexemeType::Literal(self.text(collected, true), quote),
+            None if self.is_text => LexemeType::Text(self.text(collected, false)),
+            None => LexemeType::Identifier(lowercase_name(collected), collected)
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum LexemeType
+pub enum LexemeType<'a>
 {
     BracketLeft,
     BracketRight,
-    Identifier(String),
+    // the lowercased name and how it was written
+    Identifier(Cow<'a, str>, &'a str),
+    Text(Text<'a>),
     Equals,
     EndSlash,
-    Literal(String)
+    Literal(Text<'a>, Quote),
+    Comment(&'a str),
+    Doctype(&'a str)
 }
 
 #[derive(Debug)]
-pub struct Lexeme
+pub struct Lexeme<'a>
 {
-    line: u64,
-    kind: LexemeType
+    span: Span,
+    kind: LexemeType<'a>
 }
 
-impl Lexeme
+impl<'a> Lexeme<'a>
 {
-    fn parse(state: &mut State, text: &mut TextIter) -> Self
+    fn parse(
+        state: &mut State<'a>,
+        source: &'a str,
+        text: &mut TextIter<'a>
+    ) -> Result<Self, ParseError>
     {
-        let kind = LexemeParser::new(state, text).parse();
+        let (span, mut kind) = LexemeParser::new(state, source, text).parse()?;
+
+        state.update(&kind);
+
+        if let LexemeType::Text(text) | LexemeType::Literal(text, _) = &mut kind
+        {
+            text.span = span;
+        }
 
-        Self{line: state.line, kind}
+        Ok(Self{span, kind})
     }
 
     pub fn line(&self) -> u64
     {
-        self.line
+        self.span.start.line
     }
 
-    pub fn kind(&self) -> &LexemeType
+    pub fn position(&self) -> Position
+    {
+        self.span.start
+    }
+
+    pub fn span(&self) -> Span
+    {
+        self.span
+    }
+
+    pub fn kind(&self) -> &LexemeType<'a>
     {
         &self.kind
     }
+
+    pub fn into_kind(self) -> LexemeType<'a>
+    {
+        self.kind
+    }
 }
 
-impl PartialEq for Lexeme
+impl<'a> PartialEq for Lexeme<'a>
 {
     fn eq(&self, rhs: &Self) -> bool
     {
@@ -261,7 +836,9 @@ type TextIter<'a> = Peekable<TextIterInner<'a>>;
 
 pub struct Lexer<'a>
 {
-    state: State,
+    state: State<'a>,
+    // lexemes r slices of this
+    source: &'a str,
     text: TextIter<'a>
 }
 
@@ -270,20 +847,36 @@ impl<'a> Lexer<'a>
     pub fn new(text: TextIterInner<'a>) -> Self
     {
         let state = State::default();
+        let source = text.as_str();
 
-        Self{state, text: text.peekable()}
+        Self{state, source, text: text.peekable()}
+    }
+
+    pub fn decode_entities(&mut self, decode: bool)
+    {
+        self.state.decode_entities = decode;
+    }
+
+    pub fn is_finished(&mut self) -> bool
+    {
+        self.text.peek().is_none()
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic>
+    {
+        mem::take(&mut self.state.diagnostics)
     }
 }
 
 impl<'a> Iterator for Lexer<'a>
 {
-    type Item = Lexeme;
+    type Item = Result<Lexeme<'a>, ParseError>;
 
     fn next(&mut self) -> Option<Self::Item>
     {
         if self.text.peek().is_some()
         {
-            let lexeme = Lexeme::parse(&mut self.state, &mut self.text);
+            let lexeme = Lexeme::parse(&mut self.state, self.source, &mut self.text);
 
             Some(lexeme)
         } else
@@ -0,0 +1,1030 @@
+use std::{
+    mem,
+    borrow::Cow
+};
+
+use super::{
+    unexpected_leaf,
+    Child,
+    Comment,
+    Element,
+    Attributes,
+    ParseError,
+    Position,
+    Span,
+    Diagnostic,
+    DiagnosticKind,
+    Namespace,
+    foreign::is_breakout,
+    syntaxer::{
+        Syntaxer,
+        Leaf,
+        ElementBody,
+        ElementEnd
+    }
+};
+
+
+pub const CHILDLESS_ELEMENTS: [&str; 16] = [
+    "area",
+    "base",
+    "br",
+    "col",
+    "command",
+    "embed",
+    "hr",
+    "img",
+    "input",
+    "keygen",
+    "link",
+    "meta",
+    "param",
+    "source",
+    "track",
+    "wbr"
+];
+
+// elements whose end tag can be left out when something else closes them
+const OPTIONAL_END_ELEMENTS: [&str; 21] = [
+    "p",
+    "li",
+    "dd",
+    "dt",
+    "option",
+    "optgroup",
+    "rb",
+    "rp",
+    "rt",
+    "rtc",
+    "td",
+    "th",
+    "tr",
+    "tbody",
+    "thead",
+    "tfoot",
+    "colgroup",
+    "caption",
+    "head",
+    "body",
+    "html"
+];
+
+// start tags that close an open p
+const P_CLOSERS: [&str; 41] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "center",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "header",
+    "hgroup",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "search",
+    "section",
+    "summary",
+    "ul",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "pre",
+    "listing",
+    "form",
+    "li",
+    "dd",
+    "dt",
+    "table",
+    "hr",
+    "xmp",
+    "plaintext"
+];
+
+// an end tag cant close anything outside of these
+const SCOPE_BOUNDARIES: [&str; 9] = [
+    "applet",
+    "caption",
+    "html",
+    "table",
+    "td",
+    "th",
+    "marquee",
+    "object",
+    "template"
+];
+
+const TABLE_SCOPE_BOUNDARIES: [&str; 3] = [
+    "html",
+    "table",
+    "template"
+];
+
+const TABLE_ELEMENTS: [&str; 9] = [
+    "table",
+    "tbody",
+    "thead",
+    "tfoot",
+    "tr",
+    "td",
+    "th",
+    "caption",
+    "colgroup"
+];
+
+const FORMATTING_ELEMENTS: [&str; 14] = [
+    "a",
+    "b",
+    "big",
+    "code",
+    "em",
+    "font",
+    "i",
+    "nobr",
+    "s",
+    "small",
+    "strike",
+    "strong",
+    "tt",
+    "u"
+];
+
+// formatting elements closed inside of these dont leak out
+const MARKER_ELEMENTS: [&str; 7] = [
+    "applet",
+    "object",
+    "marquee",
+    "template",
+    "td",
+    "th",
+    "caption"
+];
+
+// special elements that still reopen closed formatting elements before them
+const RECONSTRUCTING_SPECIAL_ELEMENTS: [&str; 13] = [
+    "applet",
+    "marquee",
+    "object",
+    "button",
+    "area",
+    "br",
+    "embed",
+    "img",
+    "keygen",
+    "wbr",
+    "input",
+    "select",
+    "xmp"
+];
+
+const HEAD_ELEMENTS: [&str; 11] = [
+    "base",
+    "basefont",
+    "bgsound",
+    "link",
+    "meta",
+    "noframes",
+    "script",
+    "style",
+    "template",
+    "title",
+    "noscript"
+];
+
+const SPECIAL_ELEMENTS: [&str; 83] = [
+    "address",
+    "applet",
+    "area",
+    "article",
+    "aside",
+    "base",
+    "basefont",
+    "bgsound",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "caption",
+    "center",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "iframe",
+    "img",
+    "input",
+    "keygen",
+    "li",
+    "link",
+    "listing",
+    "main",
+    "marquee",
+    "menu",
+    "meta",
+    "nav",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "ol",
+    "p",
+    "param",
+    "plaintext",
+    "pre",
+    "script",
+    "search",
+    "section",
+    "select",
+    "source",
+    "style",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "track",
+    "ul",
+    "wbr",
+    "xmp"
+];
+
+pub fn is_childless(name: &str) -> bool
+{
+    CHILDLESS_ELEMENTS.contains(&name)
+}
+
+pub fn has_optional_end(name: &str) -> bool
+{
+    OPTIONAL_END_ELEMENTS.contains(&name)
+}
+
+// a p right before this start tag gets closed by it
+pub fn closes_paragraph(name: &str) -> bool
+{
+    P_CLOSERS.contains(&name)
+}
+
+fn is_special(name: &str) -> bool
+{
+    SPECIAL_ELEMENTS.contains(&name)
+}
+
+pub fn is_formatting(name: &str) -> bool
+{
+    FORMATTING_ELEMENTS.contains(&name)
+}
+
+pub fn is_marker(name: &str) -> bool
+{
+    MARKER_ELEMENTS.contains(&name)
+}
+
+// closed formatting elements get reopened before a start tag with this name
+pub fn reconstructs(name: &str) -> bool
+{
+    !is_special(name) || RECONSTRUCTING_SPECIAL_ELEMENTS.contains(&name)
+}
+
+// foreign content wants some names in camel case
+pub fn adjust_foreign(body: &mut ElementBody<'_>, namespace: Namespace)
+{
+    if let Some(adjusted) = namespace.adjust_element(&body.name)
+    {
+        body.adjust_name(adjusted);
+    }
+
+    for tag in body.tags.iter_mut()
+    {
+        if let Some(adjusted) = namespace.adjust_attribute(tag.name())
+        {
+            tag.adjust_name(adjusted);
+        }
+    }
+}
+
+// how many of the open elements r left after a start tag with this name
+// closes everything it ends implicitly
+pub fn implied_end<T: AsRef<str>>(open: &[T], name: &str) -> usize
+{
+    let closed = match name
+    {
+        "li" => list_item(open, &["li"]),
+        "dd" | "dt" => list_item(open, &["dd", "dt"]),
+        "option" => current(open, &["option"]),
+        "optgroup" | "hr" => current(open, &["option", "optgroup"]),
+        "rb" | "rtc" => current(open, &["rb", "rp", "rt", "rtc"]),
+        "rp" | "rt" => current(open, &["rb", "rp", "rt"]),
+        "td" | "th" => in_table(open, &["td", "th"], &["tr"]),
+        "tr" => in_table(open, &["tr"], &["td", "th"]),
+        "tbody" | "thead" | "tfoot" => in_table(open, &["tbody", "thead", "tfoot"], &["td", "th", "tr"]),
+        _ => None
+    };
+
+    let mut end = closed.unwrap_or(open.len());
+
+    if closes_paragraph(name)
+    {
+        end = paragraph(&open[..end]).unwrap_or(end);
+    }
+
+    if !HEAD_ELEMENTS.contains(&name)
+    {
+        end = current(&open[..end], &["head"]).unwrap_or(end);
+    }
+
+    if name != "col" && name != "template"
+    {
+        end = current(&open[..end], &["colgroup"]).unwrap_or(end);
+    }
+
+    end
+}
+
+fn current<T: AsRef<str>>(open: &[T], names: &[&str]) -> Option<usize>
+{
+    let element = open.last()?;
+
+    names.contains(&element.as_ref()).then_some(open.len() - 1)
+}
+
+fn list_item<T: AsRef<str>>(open: &[T], names: &[&str]) -> Option<usize>
+{
+    for (index, element) in open.iter().enumerate().rev()
+    {
+        let name = element.as_ref();
+
+        if names.contains(&name)
+        {
+            return Some(index);
+        }
+
+        if SPECIAL_ELEMENTS.contains(&name) && !["address", "div", "p"].contains(&name)
+        {
+            return None;
+        }
+    }
+
+    None
+}
+
+fn in_table<T: AsRef<str>>(open: &[T], names: &[&str], passthrough: &[&str]) -> Option<usize>
+{
+    for (index, element) in open.iter().enumerate().rev()
+    {
+        let name = element.as_ref();
+
+        if names.contains(&name)
+        {
+            return Some(index);
+        }
+
+        if !passthrough.contains(&name)
+        {
+            return None;
+        }
+    }
+
+    None
+}
+
+fn paragraph<T: AsRef<str>>(open: &[T]) -> Option<usize>
+{
+    for (index, element) in open.iter().enumerate().rev()
+    {
+        let name = element.as_ref();
+
+        if name == "p"
+        {
+            return Some(index);
+        }
+
+        if SCOPE_BOUNDARIES.contains(&name) || name == "button"
+        {
+            return None;
+        }
+    }
+
+    None
+}
+
+// the open element an end tag with this name closes, if its in scope
+pub fn find_in_scope<T: AsRef<str>>(open: &[T], name: &str) -> Option<usize>
+{
+    let is_boundary = |other: &str|
+    {
+        if TABLE_ELEMENTS.contains(&name)
+        {
+            TABLE_SCOPE_BOUNDARIES.contains(&other)
+        } else if name == "p"
+        {
+            SCOPE_BOUNDARIES.contains(&other) || other == "button"
+        } else if name == "li"
+        {
+            SCOPE_BOUNDARIES.contains(&other) || other == "ol" || other == "ul"
+        } else if is_special(name) || is_formatting(name)
+        {
+            SCOPE_BOUNDARIES.contains(&other)
+        } else
+        {
+            is_special(other)
+        }
+    };
+
+    for (index, element) in open.iter().enumerate().rev()
+    {
+        let other = element.as_ref();
+
+        if other == name
+        {
+            return Some(index);
+        }
+
+        if is_boundary(other)
+        {
+            return None;
+        }
+    }
+
+    None
+}
+
+struct OpenElement<'a>
+{
+    body: ElementBody<'a>,
+    children: Vec<Child<'a>>,
+    // the end of its end tag, if it had one
+    end: Option<Position>,
+    // misnesting split it up so its span doesnt match the text anymore
+    is_reconstructed: bool,
+    namespace: Namespace
+}
+
+impl<'a> OpenElement<'a>
+{
+    fn new(body: ElementBody<'a>, namespace: Namespace) -> Self
+    {
+        Self{body, children: Vec::new(), end: None, is_reconstructed: false, namespace}
+    }
+
+    // only html formatting elements get reconstructed
+    fn reconstructed(body: ElementBody<'a>) -> Self
+    {
+        Self{is_reconstructed: true, ..Self::new(body, Namespace::Html)}
+    }
+
+    fn name(&self) -> &str
+    {
+        &self.body.name
+    }
+
+    fn is_integration_point(&self) -> bool
+    {
+        self.namespace.is_integration_point(self.name(), &self.body.tags)
+    }
+
+    fn close(self) -> Element<'a>
+    {
+        let end = self.end.unwrap_or_else(||
+        {
+            self.children.iter()
+                .filter_map(|child| child.span())
+                .map(|span| span.end)
+                .fold(self.body.span.end, Position::max)
+        });
+
+        let span = Span::new(self.body.span.start, end);
+        let tags = Attributes::from_tags(self.body.tags.into_vec());
+
+        Element{
+            name: self.body.name,
+            spelling: self.body.spelling,
+            namespace: self.namespace,
+            namespace_uri: None,
+            span,
+            tags,
+            children: self.children,
+            is_modified: false,
+            is_reconstructed: self.is_reconstructed
+        }
+    }
+}
+
+impl<'a> AsRef<str> for OpenElement<'a>
+{
+    fn as_ref(&self) -> &str
+    {
+        self.name()
+    }
+}
+
+// a formatting element that something else closed, it gets reopened when more content shows up
+struct Pending<'a>
+{
+    body: ElementBody<'a>,
+    depth: usize
+}
+
+pub struct TreeBuilder<'a>
+{
+    open: Vec<OpenElement<'a>>,
+    pending: Vec<Pending<'a>>,
+    children: Vec<Child<'a>>,
+    diagnostics: Vec<Diagnostic>
+}
+
+impl<'a> TreeBuilder<'a>
+{
+    pub fn new() -> Self
+    {
+        Self{
+            open: Vec::new(),
+            pending: Vec::new(),
+            children: Vec::new(),
+            diagnostics: Vec::new()
+        }
+    }
+
+    // parses one element and everything that ends up inside of it
+    pub fn parse_element(mut self, leaves: &mut Syntaxer<'a>) -> Result<Element<'a>, ParseError>
+    {
+        match leaves.next().transpose()?
+        {
+            Some(Leaf::Body(body)) => self.start(body),
+            leaf => return Err(unexpected_leaf(leaf, "Body"))
+        }
+
+        while !self.open.is_empty()
+        {
+            if let Some(Ok(Leaf::Body(body))) = leaves.peek()
+            {
+                // if the start tag closes the element its not a part of it
+                self.close_implied(&body.name);
+
+                if self.open.is_empty()
+                {
+                    break;
+                }
+            }
+
+            match leaves.next().transpose()?
+            {
+                Some(leaf) => self.process(leaf),
+                None => break
+            }
+        }
+
+        let (mut children, _) = self.finish();
+
+        match children.pop()
+        {
+            Some(Child::Element(element)) => Ok(element),
+            _ => unreachable!()
+        }
+    }
+
+    pub fn has_elements(&self) -> bool
+    {
+        !self.open.is_empty() || self.children.iter().any(|child| child.element().is_some())
+    }
+
+    pub fn process(&mut self, leaf: Leaf<'a>)
+    {
+        match leaf
+        {
+            Leaf::Body(body) => self.start(body),
+            Leaf::End(end) => self.end(end),
+            Leaf::Content(text) =>
+            {
+                // formatting elements closed by an adoption at the top still apply to the text after,
+                // whitespace doesnt reopen them but its kept like any other text
+                if !text.raw().trim().is_empty()
+                {
+                    self.reconstruct();
+                }
+
+                self.push(Child::Text(text));
+            },
+            Leaf::Comment(text, span) => self.push(Child::Comment(Comment{text: Cow::Borrowed(text), span})),
+            // a doctype anywhere but the start means nothing
+            Leaf::Doctype(_) => ()
+        }
+    }
+
+    pub fn finish(mut self) -> (Vec<Child<'a>>, Vec<Diagnostic>)
+    {
+        for element in &self.open
+        {
+            if !has_optional_end(element.name())
+            {
+                let diagnostic = Diagnostic::new(
+                    DiagnosticKind::UnclosedElement,
+                    element.name(),
+                    element.body.position
+                );
+
+                self.diagnostics.push(diagnostic);
+            }
+        }
+
+        self.close_to(0);
+
+        (self.children, self.diagnostics)
+    }
+
+    fn diagnose(&mut self, kind: DiagnosticKind, name: &str, position: Position)
+    {
+        self.diagnostics.push(Diagnostic::new(kind, name, position));
+    }
+
+    fn push(&mut self, child: Child<'a>)
+    {
+        match self.open.last_mut()
+        {
+            Some(parent) => parent.children.push(child),
+            None => self.children.push(child)
+        }
+    }
+
+    fn start(&mut self, mut body: ElementBody<'a>)
+    {
+        let namespace = self.namespace_for(&body);
+
+        if namespace.is_foreign()
+        {
+            adjust_foreign(&mut body, namespace);
+
+            // its like xml in here, nothing gets closed implicitly and anything can have children
+            self.open.push(OpenElement::new(body, namespace));
+
+            return;
+        }
+
+        self.close_implied(&body.name);
+
+        // links cant be nested so an open one gets closed
+        if body.name == "a" && self.is_open("a")
+        {
+            self.diagnose(DiagnosticKind::MisnestedElement, "a", body.position);
+            self.adopt("a");
+        }
+
+        if reconstructs(&body.name)
+        {
+            self.reconstruct();
+        }
+
+        let is_childless = is_childless(&body.name);
+
+        self.open.push(OpenElement::new(body, Namespace::Html));
+
+        if is_childless
+        {
+            self.close_to(self.open.len() - 1);
+        }
+    }
+
+    fn end(&mut self, mut end: ElementEnd<'a>)
+    {
+        // the end tag got lowercased too so it needs the same fixing to match
+        if let Some(adjusted) = Namespace::Svg.adjust_element(&end.name).filter(|_| self.is_open("svg"))
+        {
+            end.name = Cow::Borrowed(adjusted);
+        }
+
+        if let Some(index) = self.foreign_end(&end.name)
+        {
+            self.open[index].end = Some(end.span.end);
+            self.close_to(index);
+
+            return;
+        }
+
+        // the end of a childless element is just the end of <br/> and such
+        if is_childless(&end.name) && !self.is_open(&end.name)
+        {
+            return;
+        }
+
+        // only foreign elements can close themselves, on html ones the / means nothing
+        if end.is_self_closing
+        {
+            self.diagnose(DiagnosticKind::SelfClosingElement, &end.name, end.position);
+
+            return;
+        }
+
+        if is_formatting(&end.name)
+        {
+            self.end_formatting(end);
+        } else
+        {
+            self.end_other(end);
+        }
+    }
+
+    fn end_formatting(&mut self, end: ElementEnd<'a>)
+    {
+        let index = match self.open.iter().rposition(|element| element.name() == end.name)
+        {
+            Some(x) => x,
+            None =>
+            {
+                // the element already got closed by something else
+                match self.pending.iter().rposition(|pending| pending.body.name == end.name)
+                {
+                    Some(pending) =>
+                    {
+                        self.pending.remove(pending);
+                    },
+                    None => self.diagnose(DiagnosticKind::StrayEnd, &end.name, end.position)
+                }
+
+                return;
+            }
+        };
+
+        let is_in_scope = !self.open[index + 1..].iter().any(|element|
+        {
+            SCOPE_BOUNDARIES.contains(&element.name())
+        });
+
+        if !is_in_scope
+        {
+            self.diagnose(DiagnosticKind::StrayEnd, &end.name, end.position);
+
+            return;
+        }
+
+        if index + 1 != self.open.len()
+        {
+            self.diagnose(DiagnosticKind::MisnestedElement, &end.name, end.position);
+        }
+
+        self.open[index].end = Some(end.span.end);
+
+        self.adopt(&end.name);
+    }
+
+    // roughly the adoption agency from the spec, the part of the misnested element
+    // that ended up inside a block gets moved into a copy of it
+    fn adopt(&mut self, name: &str)
+    {
+        for _ in 0..8
+        {
+            let index = match self.open.iter().rposition(|element| element.name() == name)
+            {
+                Some(x) => x,
+                None => return
+            };
+
+            let block = (index + 1..self.open.len()).find(|index|
+            {
+                is_special(self.open[*index].name())
+            });
+
+            let block = match block
+            {
+                Some(x) => x,
+                None =>
+                {
+                    self.close_to(index);
+
+                    return;
+                }
+            };
+
+            let mut above = self.open.split_off(block).into_iter();
+
+            let formatting = self.open[index].body.clone();
+            let copies = self.open[index + 1..].iter()
+                .filter(|element| is_formatting(element.name()))
+                .map(|element| element.body.clone())
+                .collect::<Vec<_>>();
+
+            self.open[index].is_reconstructed = true;
+            self.close_elements(index, false);
+
+            self.open.extend(copies.into_iter().map(OpenElement::reconstructed));
+
+            let mut block = above.next().unwrap();
+
+            let mut inner = OpenElement::reconstructed(formatting);
+            inner.children = mem::take(&mut block.children);
+
+            self.open.push(block);
+            self.open.push(inner);
+            self.open.extend(above);
+        }
+    }
+
+    fn end_other(&mut self, end: ElementEnd<'a>)
+    {
+        let name: &str = &end.name;
+
+        let index = match find_in_scope(&self.open, name)
+        {
+            Some(x) => x,
+            None =>
+            {
+                self.diagnose(DiagnosticKind::StrayEnd, name, end.position);
+
+                return;
+            }
+        };
+
+        let unclosed = self.open[index + 1..].iter()
+            .filter(|element| !has_optional_end(element.name()))
+            .map(|element| Diagnostic::new(
+                DiagnosticKind::UnclosedElement,
+                element.name(),
+                element.body.position
+            ))
+            .collect::<Vec<_>>();
+
+        self.diagnostics.extend(unclosed);
+
+        self.open[index].end = Some(end.span.end);
+
+        self.close_to(index);
+    }
+
+    fn reconstruct(&mut self)
+    {
+        let marker = self.open.iter().rposition(|element|
+        {
+            MARKER_ELEMENTS.contains(&element.name())
+        });
+
+        // stuff from outside of a marker doesnt get reopened inside of it
+        let (ready, blocked): (Vec<_>, Vec<_>) = mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|pending| marker.map(|marker| marker < pending.depth).unwrap_or(true));
+
+        self.pending = blocked;
+
+        self.open.extend(ready.into_iter().map(|pending| OpenElement::reconstructed(pending.body)));
+    }
+
+    // the namespace a start tag ends up in, html elements that cant be in foreign content close it
+    fn namespace_for(&mut self, body: &ElementBody<'a>) -> Namespace
+    {
+        let name = body.name.as_ref();
+
+        let (namespace, current, is_integration_point) = match self.open.last()
+        {
+            Some(current) => (current.namespace, current.name(), current.is_integration_point()),
+            None => return Namespace::from_root(name).unwrap_or_default()
+        };
+
+        // mglyph and malignmark stay mathml in the text ones like mi but not in annotation-xml
+        let is_mathml_text = namespace == Namespace::MathMl && current != "annotation-xml";
+
+        let is_html_content = !namespace.is_foreign() || (is_integration_point
+            && !(is_mathml_text && matches!(name, "mglyph" | "malignmark")));
+
+        if is_html_content
+        {
+            return Namespace::from_root(name).unwrap_or_default();
+        }
+
+        if namespace == Namespace::MathMl && current == "annotation-xml" && name == "svg"
+        {
+            return Namespace::Svg;
+        }
+
+        if is_breakout(name, &body.tags)
+        {
+            self.diagnose(DiagnosticKind::MisnestedElement, name, body.position);
+
+            let index = self.open.iter()
+                .rposition(|element| !element.namespace.is_foreign() || element.is_integration_point())
+                .map(|index| index + 1)
+                .unwrap_or(0);
+
+            self.close_to(index);
+
+            return Namespace::Html;
+        }
+
+        namespace
+    }
+
+    // end tags in foreign content close the closest element with the same name, as long as its foreign
+    fn foreign_end(&self, name: &str) -> Option<usize>
+    {
+        for (index, element) in self.open.iter().enumerate().rev()
+        {
+            if !element.namespace.is_foreign()
+            {
+                return None;
+            }
+
+            if element.name().eq_ignore_ascii_case(name)
+            {
+                return Some(index);
+            }
+        }
+
+        None
+    }
+
+    fn is_open(&self, name: &str) -> bool
+    {
+        self.open.iter().any(|element| element.name() == name)
+    }
+
+    // closes every element starting from the one at index
+    fn close_to(&mut self, index: usize)
+    {
+        self.close_elements(index, true);
+    }
+
+    fn close_elements(&mut self, index: usize, reopen: bool)
+    {
+        let marker = self.open[index..].iter()
+            .position(|element| MARKER_ELEMENTS.contains(&element.name()))
+            .map(|marker| marker + index);
+
+        if let Some(marker) = marker
+        {
+            self.pending.retain(|pending| pending.depth <= marker);
+        }
+
+        if reopen
+        {
+            let end = marker.unwrap_or(self.open.len());
+
+            let reopened = (index + 1..end).filter(|depth|
+            {
+                let element = &self.open[*depth];
+
+                !element.namespace.is_foreign() && is_formatting(element.name())
+            }).map(|depth|
+            {
+                Pending{body: self.open[depth].body.clone(), depth}
+            }).collect::<Vec<_>>();
+
+            self.pending.extend(reopened);
+        }
+
+        while self.open.len() > index
+        {
+            let element = self.open.pop().unwrap().close();
+
+            self.push(Child::Element(element));
+        }
+    }
+
+    // closes the elements that a start tag with this name ends implicitly
+    fn close_implied(&mut self, name: &str)
+    {
+        let end = implied_end(&self.open, name);
+
+        if end < self.open.len()
+        {
+            self.close_to(end);
+        }
+    }
+}
@@ -0,0 +1,1099 @@
+// strict xml, anything thats not well formed is an error instead of getting recovered from
+// it builds the same tree as html so everything that works on documents works on these too
+
+use std::borrow::Cow;
+
+use super::{
+    Child,
+    Comment,
+    Element,
+    Document,
+    Doctype,
+    Attributes,
+    Namespace,
+    Tag,
+    Text,
+    Quote,
+    Position,
+    Span,
+    XmlError,
+    XmlErrorKind
+};
+
+
+// theres no dtd support so these r the only named ones
+const PREDEFINED_ENTITIES: [(&str, char); 5] = [
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\'')
+];
+
+// the pseudo attributes of the declaration in the order they have to be in
+const DECLARATION_ATTRIBUTES: [&str; 3] = ["version", "encoding", "standalone"];
+
+fn is_xml_char(c: char) -> bool
+{
+    matches!(c,
+        '\t' | '\n' | '\r'
+        | '\u{20}'..='\u{D7FF}'
+        | '\u{E000}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{10FFFF}')
+}
+
+fn is_xml_whitespace(c: char) -> bool
+{
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn is_name_start(c: char) -> bool
+{
+    matches!(c,
+        ':' | '_' | 'A'..='Z' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+fn is_name_char(c: char) -> bool
+{
+    is_name_start(c) || matches!(c,
+        '-' | '.' | '0'..='9'
+        | '\u{B7}'
+        | '\u{300}'..='\u{36F}'
+        | '\u{203F}'..='\u{2040}')
+}
+
+// with namespaces a name can have one colon, and not at either end
+fn is_qualified_name(name: &str) -> bool
+{
+    match name.split_once(':')
+    {
+        Some((prefix, local)) => !prefix.is_empty() && !local.is_empty() && !local.contains(':'),
+        None => true
+    }
+}
+
+fn describe(c: char) -> String
+{
+    format!("U+{:04X}", c as u32)
+}
+
+// the <?xml ?> at the start of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlDeclaration
+{
+    version: String,
+    encoding: Option<String>,
+    standalone: Option<bool>
+}
+
+impl XmlDeclaration
+{
+    pub fn version(&self) -> &str
+    {
+        &self.version
+    }
+
+    // just the label like it was written, the actual encoding is on the document
+    pub fn encoding(&self) -> Option<&str>
+    {
+        self.encoding.as_deref()
+    }
+
+    pub fn standalone(&self) -> Option<bool>
+    {
+        self.standalone
+    }
+}
+
+// <?target data?>, like the xml-stylesheet ones in feeds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessingInstruction<'a>
+{
+    target: Cow<'a, str>,
+    data: Cow<'a, str>,
+    span: Span
+}
+
+impl<'a> ProcessingInstruction<'a>
+{
+    pub fn new(target: impl Into<Cow<'a, str>>, data: impl Into<Cow<'a, str>>) -> Self
+    {
+        Self{target: target.into(), data: data.into(), span: Span::default()}
+    }
+
+    pub fn target(&self) -> &str
+    {
+        &self.target
+    }
+
+    pub fn data(&self) -> &str
+    {
+        &self.data
+    }
+
+    pub fn span(&self) -> Span
+    {
+        self.span
+    }
+
+    pub fn into_owned(self) -> ProcessingInstruction<'static>
+    {
+        ProcessingInstruction{
+            target: Cow::Owned(self.target.into_owned()),
+            data: Cow::Owned(self.data.into_owned()),
+            span: self.span
+        }
+    }
+}
+
+// a tag along with the parts namespaces need, borrowed from the text when they can be
+struct Attribute<'a>
+{
+    name: &'a str,
+    value: Cow<'a, str>,
+    tag: Tag<'a>
+}
+
+struct OpenElement<'a>
+{
+    name: &'a str,
+    namespace_uri: Cow<'a, str>,
+    start: Position,
+    tags: Vec<Tag<'a>>,
+    children: Vec<Child<'a>>,
+    // how many namespace bindings there were before this element added its own
+    bindings: usize
+}
+
+impl<'a> OpenElement<'a>
+{
+    fn close(self, end: Position) -> Element<'a>
+    {
+        let namespace = Namespace::from_uri(&self.namespace_uri).unwrap_or(Namespace::Other);
+
+        Element{
+            name: Cow::Borrowed(self.name),
+            spelling: None,
+            namespace,
+            namespace_uri: Some(self.namespace_uri),
+            span: Span::new(self.start, end),
+            tags: Attributes::from_vec(self.tags),
+            children: self.children,
+            is_modified: false,
+            is_reconstructed: false
+        }
+    }
+}
+
+enum Started<'a>
+{
+    Open(OpenElement<'a>),
+    // <name/>, theres nothing more to it
+    Closed(Element<'a>)
+}
+
+pub struct XmlParser<'a>
+{
+    source: &'a str,
+    position: Position,
+    skip_comments: bool,
+    decode_entities: bool,
+    // the prefixes in scope and what they stand for, the empty prefix is the default namespace
+    bindings: Vec<(&'a str, Cow<'a, str>)>
+}
+
+impl<'a> XmlParser<'a>
+{
+    pub fn new(source: &'a str) -> Self
+    {
+        Self{
+            source,
+            position: Position::default(),
+            skip_comments: false,
+            decode_entities: true,
+            bindings: Vec::new()
+        }
+    }
+
+    pub fn skip_comments(&mut self, skip: bool)
+    {
+        self.skip_comments = skip;
+    }
+
+    pub fn decode_entities(&mut self, decode: bool)
+    {
+        self.decode_entities = decode;
+    }
+
+    pub fn parse(mut self) -> Result<Document<'a>, XmlError>
+    {
+        // a byte order mark thats still there after decoding
+        if self.starts_with("\u{FEFF}")
+        {
+            self.skip("\u{FEFF}");
+        }
+
+        let declaration = if self.is_declaration_start()
+        {
+            Some(self.parse_declaration()?)
+        } else
+        {
+            None
+        };
+
+        let mut doctype = None;
+        let mut children = Vec::new();
+        let mut has_root = false;
+
+        loop
+        {
+            let start = self.position;
+            if self.skip_whitespace()
+            {
+                // whitespace between top level nodes is kept like in html
+                let raw = &self.source[start.offset..self.position.offset];
+
+                children.push(Child::Text(Text::from_parts(raw, None, Span::new(start, self.position))));
+
+                continue;
+            }
+
+            if self.peek().is_none()
+            {
+                break;
+            }
+
+            if self.starts_with("<!--")
+            {
+                children.extend(self.parse_comment()?);
+            } else if self.starts_with("<?")
+            {
+                children.push(self.parse_instruction()?);
+            } else if has_root
+            {
+                return Err(self.error(XmlErrorKind::ContentAfterRoot, self.describe_next()));
+            } else if self.starts_with("<!DOCTYPE") && doctype.is_none()
+            {
+                doctype = Some(self.parse_doctype()?);
+            } else if self.starts_with("<") && !self.starts_with("<!")
+            {
+                children.push(Child::Element(self.parse_element()?));
+
+                has_root = true;
+            } else
+            {
+                return Err(self.unexpected("the document"));
+            }
+        }
+
+        if !has_root
+        {
+            return Err(self.error(XmlErrorKind::MissingRoot, ""));
+        }
+
+        Ok(Document{
+            doctype,
+            declaration,
+            encoding: None,
+            children,
+            diagnostics: Box::new([]),
+            is_modified: false
+        })
+    }
+
+    fn rest(&self) -> &'a str
+    {
+        &self.source[self.position.offset..]
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.rest().chars().next()
+    }
+
+    fn starts_with(&self, s: &str) -> bool
+    {
+        self.rest().starts_with(s)
+    }
+
+    fn advance(&mut self) -> Option<char>
+    {
+        let c = self.peek()?;
+
+        self.position.advance(c);
+
+        Some(c)
+    }
+
+    // only for text thats known to come next
+    fn skip(&mut self, s: &str)
+    {
+        self.position = self.position.advanced(s);
+    }
+
+    fn error(&self, kind: XmlErrorKind, found: impl Into<String>) -> XmlError
+    {
+        XmlError::new(kind, found, self.position)
+    }
+
+    fn describe_next(&self) -> String
+    {
+        match self.peek()
+        {
+            Some('<') =>
+            {
+                let name = self.rest()[1..].chars().take_while(|c| is_name_char(*c)).collect::<String>();
+
+                format!("<{name}")
+            },
+            Some(_) => "text".to_owned(),
+            None => "nothing".to_owned()
+        }
+    }
+
+    // inside is whatever was being parsed, for when the text ends
+    fn unexpected(&self, inside: &str) -> XmlError
+    {
+        match self.peek()
+        {
+            Some(c) if !is_xml_char(c) => self.error(XmlErrorKind::InvalidChar, describe(c)),
+            Some(c) => self.error(XmlErrorKind::UnexpectedChar, format!("{c:?}")),
+            None => self.error(XmlErrorKind::UnexpectedEnd, inside)
+        }
+    }
+
+    fn expect(&mut self, s: &str, inside: &str) -> Result<(), XmlError>
+    {
+        if self.starts_with(s)
+        {
+            self.skip(s);
+
+            Ok(())
+        } else
+        {
+            Err(self.unexpected(inside))
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool
+    {
+        let start = self.position.offset;
+        while self.peek().is_some_and(is_xml_whitespace)
+        {
+            self.advance();
+        }
+
+        self.position.offset != start
+    }
+
+    // moves over text thats already been found, making sure its all allowed in xml
+    fn consume(&mut self, text: &str) -> Result<(), XmlError>
+    {
+        for c in text.chars()
+        {
+            if !is_xml_char(c)
+            {
+                return Err(self.error(XmlErrorKind::InvalidChar, describe(c)));
+            }
+
+            self.position.advance(c);
+        }
+
+        Ok(())
+    }
+
+    // everything up to the terminator, which gets skipped
+    fn collect_until(&mut self, terminator: &str, inside: &str) -> Result<&'a str, XmlError>
+    {
+        let rest = self.rest();
+
+        let content = match rest.find(terminator)
+        {
+            Some(length) => &rest[..length],
+            None =>
+            {
+                self.consume(rest)?;
+
+                return Err(self.error(XmlErrorKind::UnexpectedEnd, inside));
+            }
+        };
+
+        self.consume(content)?;
+        self.skip(terminator);
+
+        Ok(content)
+    }
+
+    fn parse_name(&mut self) -> Result<&'a str, XmlError>
+    {
+        let start = self.position.offset;
+
+        match self.peek()
+        {
+            Some(c) if is_name_start(c) => self.advance(),
+            _ => return Err(self.unexpected("a name"))
+        };
+
+        while self.peek().is_some_and(is_name_char)
+        {
+            self.advance();
+        }
+
+        Ok(&self.source[start..self.position.offset])
+    }
+
+    fn parse_quoted(&mut self, inside: &str) -> Result<&'a str, XmlError>
+    {
+        let quote = match self.peek()
+        {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return Err(self.unexpected(inside))
+        };
+
+        self.advance();
+
+        self.collect_until(&quote.to_string(), inside)
+    }
+
+    // <?xml-stylesheet ?> is a processing instruction, not a declaration
+    fn is_declaration_start(&self) -> bool
+    {
+        self.rest().strip_prefix("<?xml").is_some_and(|rest|
+        {
+            rest.starts_with(is_xml_whitespace) || rest.starts_with("?>")
+        })
+    }
+
+    // <?xml version="1.0" encoding="UTF-8" standalone="yes"?>, in that order
+    fn parse_declaration(&mut self) -> Result<XmlDeclaration, XmlError>
+    {
+        let start = self.position;
+        let inside = "the xml declaration";
+
+        self.skip("<?xml");
+
+        let mut declaration = XmlDeclaration{version: String::new(), encoding: None, standalone: None};
+        let mut expected = DECLARATION_ATTRIBUTES.iter();
+
+        loop
+        {
+            let has_space = self.skip_whitespace();
+
+            if self.starts_with("?>")
+            {
+                self.skip("?>");
+
+                break;
+            }
+
+            if !has_space
+            {
+                return Err(self.unexpected(inside));
+            }
+
+            let position = self.position;
+            let name = self.parse_name()?;
+
+            self.skip_whitespace();
+            self.expect("=", inside)?;
+            self.skip_whitespace();
+
+            let value = self.parse_quoted(inside)?;
+
+            let invalid = |found: String| XmlError::new(XmlErrorKind::InvalidDeclaration, found, position);
+
+            // the iterator only goes forward so anything out of order or repeated wont be found
+            if !expected.any(|expected| *expected == name)
+            {
+                return Err(invalid(format!("{name} is out of place")));
+            }
+
+            match name
+            {
+                "version" =>
+                {
+                    let is_valid = value.strip_prefix("1.").is_some_and(|minor|
+                    {
+                        !minor.is_empty() && minor.bytes().all(|c| c.is_ascii_digit())
+                    });
+
+                    if !is_valid
+                    {
+                        return Err(invalid(format!("{value} isnt an xml version")));
+                    }
+
+                    declaration.version = value.to_owned();
+                },
+                "encoding" =>
+                {
+                    let is_valid = value.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+
+                    if !is_valid
+                    {
+                        return Err(invalid(format!("{value} isnt an encoding name")));
+                    }
+
+                    declaration.encoding = Some(value.to_owned());
+                },
+                _ =>
+                {
+                    declaration.standalone = Some(match value
+                    {
+                        "yes" => true,
+                        "no" => false,
+                        _ => return Err(invalid(format!("standalone has to be yes or no, not {value}")))
+                    });
+                }
+            }
+        }
+
+        if declaration.version.is_empty()
+        {
+            return Err(XmlError::new(XmlErrorKind::InvalidDeclaration, "it has no version", start));
+        }
+
+        Ok(declaration)
+    }
+
+    // the internal subset in [] gets skipped, entities declared in there arent supported
+    fn parse_doctype(&mut self) -> Result<Doctype, XmlError>
+    {
+        self.skip("<!DOCTYPE");
+
+        let start = self.position.offset;
+        let mut subset_start = None;
+        let mut is_subset = false;
+        let mut quote = None;
+
+        loop
+        {
+            let c = match self.peek()
+            {
+                Some(c) if is_xml_char(c) => c,
+                _ => return Err(self.unexpected("the doctype"))
+            };
+
+            self.advance();
+
+            match (quote, c)
+            {
+                (Some(quote_char), c) if c == quote_char => quote = None,
+                (Some(_), _) => (),
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '[') if !is_subset =>
+                {
+                    is_subset = true;
+                    subset_start.get_or_insert(self.position.offset - 1);
+                },
+                (None, ']') => is_subset = false,
+                (None, '>') if !is_subset => break,
+                _ => ()
+            }
+        }
+
+        let end = subset_start.unwrap_or(self.position.offset - 1);
+
+        Ok(Doctype::parse(&self.source[start..end]))
+    }
+
+    fn parse_comment(&mut self) -> Result<Option<Child<'a>>, XmlError>
+    {
+        let start = self.position;
+
+        self.skip("<!--");
+
+        let comment = self.collect_until("--", "a comment")?;
+
+        // -- cant be anywhere in a comment except for the end
+        if !self.starts_with(">")
+        {
+            return Err(self.error(XmlErrorKind::UnexpectedChar, "-- inside of a comment"));
+        }
+
+        self.skip(">");
+
+        let comment = Comment{text: Cow::Borrowed(comment), span: Span::new(start, self.position)};
+
+        Ok((!self.skip_comments).then_some(Child::Comment(comment)))
+    }
+
+    fn parse_instruction(&mut self) -> Result<Child<'a>, XmlError>
+    {
+        let start = self.position;
+        let inside = "a processing instruction";
+
+        self.skip("<?");
+
+        let target_position = self.position;
+        let target = self.parse_name()?;
+
+        if target.eq_ignore_ascii_case("xml")
+        {
+            return Err(XmlError::new(XmlErrorKind::InvalidDeclaration, "it has to be at the very start", start));
+        }
+
+        if target.contains(':')
+        {
+            return Err(XmlError::new(XmlErrorKind::InvalidName, target, target_position));
+        }
+
+        let data = if self.starts_with("?>")
+        {
+            self.skip("?>");
+
+            ""
+        } else
+        {
+            if !self.skip_whitespace()
+            {
+                return Err(self.unexpected(inside));
+            }
+
+            self.collect_until("?>", inside)?
+        };
+
+        Ok(Child::ProcessingInstruction(ProcessingInstruction{
+            target: Cow::Borrowed(target),
+            data: Cow::Borrowed(data),
+            span: Span::new(start, self.position)
+        }))
+    }
+
+    // the text inside is taken as is, raw keeps the whole section so it serializes back the same
+    fn parse_cdata(&mut self) -> Result<Child<'a>, XmlError>
+    {
+        let start = self.position;
+
+        self.skip("<![CDATA[");
+
+        let content = self.collect_until("]]>", "a cdata section")?;
+        let raw = &self.source[start.offset..self.position.offset];
+
+        Ok(Child::Text(Text::from_parts(raw, Some(content.to_owned()), Span::new(start, self.position))))
+    }
+
+    // text up to the next <, whitespace between elements gets dropped like in html
+    fn parse_text(&mut self) -> Result<Option<Child<'a>>, XmlError>
+    {
+        let start = self.position;
+
+        let rest = self.rest();
+        let raw = &rest[..rest.find('<').unwrap_or(rest.len())];
+
+        if let Some(index) = raw.find("]]>")
+        {
+            let position = start.advanced(&raw[..index]);
+
+            return Err(XmlError::new(XmlErrorKind::UnexpectedChar, "]]> outside of a cdata section", position));
+        }
+
+        if raw.is_empty()
+        {
+            return Ok(None);
+        }
+
+        // whitespace only text is kept too, in xml all of it is content
+        let decoded = self.decode(raw, false)?;
+
+        Ok(Some(Child::Text(Text::from_parts(raw, decoded, Span::new(start, self.position)))))
+    }
+
+    // moves over text and decodes references in it, in attributes whitespace also turns into spaces
+    // nothing comes back if the decoded text would be the same as the raw text
+    fn decode(&mut self, raw: &'a str, is_attribute: bool) -> Result<Option<String>, XmlError>
+    {
+        let mut decoded = String::with_capacity(raw.len());
+        let mut is_changed = false;
+
+        let mut rest = raw;
+        while let Some(c) = rest.chars().next()
+        {
+            if c == '&'
+            {
+                let (replacement, length) = self.parse_reference(rest)?;
+
+                decoded.push(replacement);
+                is_changed = true;
+
+                self.skip(&rest[..length]);
+                rest = &rest[length..];
+
+                continue;
+            }
+
+            if !is_xml_char(c)
+            {
+                return Err(self.error(XmlErrorKind::InvalidChar, describe(c)));
+            }
+
+            // line breaks r always just \n
+            let is_crlf = c == '\r' && rest[1..].starts_with('\n');
+
+            match c
+            {
+                '\r' if is_crlf => is_changed = true,
+                '\t' | '\n' | '\r' if is_attribute =>
+                {
+                    decoded.push(' ');
+                    is_changed = true;
+                },
+                '\r' =>
+                {
+                    decoded.push('\n');
+                    is_changed = true;
+                },
+                c => decoded.push(c)
+            }
+
+            self.position.advance(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        Ok((is_changed && self.decode_entities).then_some(decoded))
+    }
+
+    // a reference at the start of text, the character and how long the reference was
+    fn parse_reference(&self, text: &str) -> Result<(char, usize), XmlError>
+    {
+        let length = text[1..].find(|c: char| !(is_name_char(c) || c == '#')).map(|x| x + 1).unwrap_or(text.len());
+
+        let name = &text[1..length];
+        if !text[length..].starts_with(';') || name.is_empty()
+        {
+            return Err(self.error(XmlErrorKind::UnknownEntity, format!("&{name}")));
+        }
+
+        let reference = &text[..=length];
+
+        if let Some(number) = name.strip_prefix('#')
+        {
+            let value = match number.strip_prefix('x')
+            {
+                Some(hex) if !hex.is_empty() && hex.bytes().all(|c| c.is_ascii_hexdigit()) =>
+                {
+                    u32::from_str_radix(hex, 16).ok()
+                },
+                None if !number.is_empty() && number.bytes().all(|c| c.is_ascii_digit()) =>
+                {
+                    number.parse().ok()
+                },
+                _ => None
+            };
+
+            return value.and_then(char::from_u32).filter(|c| is_xml_char(*c))
+                .map(|c| (c, reference.len()))
+                .ok_or_else(|| self.error(XmlErrorKind::InvalidChar, reference));
+        }
+
+        PREDEFINED_ENTITIES.iter()
+            .find(|(entity, _)| *entity == name)
+            .map(|(_, c)| (*c, reference.len()))
+            .ok_or_else(|| self.error(XmlErrorKind::UnknownEntity, reference))
+    }
+
+    fn parse_element(&mut self) -> Result<Element<'a>, XmlError>
+    {
+        let mut open: Vec<OpenElement<'a>> = Vec::new();
+
+        loop
+        {
+            let child = if self.starts_with("</")
+            {
+                Some(Child::Element(self.parse_end(open.pop())?))
+            } else if self.starts_with("<!--")
+            {
+                self.parse_comment()?
+            } else if self.starts_with("<![CDATA[")
+            {
+                Some(self.parse_cdata()?)
+            } else if self.starts_with("<?")
+            {
+                Some(self.parse_instruction()?)
+            } else if self.starts_with("<!")
+            {
+                return Err(self.error(XmlErrorKind::UnexpectedChar, "<!"));
+            } else if self.starts_with("<")
+            {
+                match self.parse_start()?
+                {
+                    Started::Open(element) =>
+                    {
+                        open.push(element);
+
+                        None
+                    },
+                    Started::Closed(element) => Some(Child::Element(element))
+                }
+            } else if let Some(element) = open.last().filter(|_| self.peek().is_none())
+            {
+                return Err(XmlError::new(XmlErrorKind::UnclosedElement, element.name, element.start));
+            } else
+            {
+                self.parse_text()?
+            };
+
+            if let Some(child) = child
+            {
+                match (open.last_mut(), child)
+                {
+                    (Some(parent), child) => parent.children.push(child),
+                    (None, Child::Element(element)) => return Ok(element),
+                    (None, _) => unreachable!("theres always an element open around other children")
+                }
+            }
+        }
+    }
+
+    fn parse_start(&mut self) -> Result<Started<'a>, XmlError>
+    {
+        let start = self.position;
+
+        self.skip("<");
+
+        let name = self.parse_name()?;
+
+        if !is_qualified_name(name)
+        {
+            return Err(XmlError::new(XmlErrorKind::InvalidName, name, start));
+        }
+
+        let inside = format!("the {name} start tag");
+
+        let mut attributes: Vec<Attribute<'a>> = Vec::new();
+        let is_empty = loop
+        {
+            let has_space = self.skip_whitespace();
+
+            if self.starts_with("/>")
+            {
+                self.skip("/>");
+
+                break true;
+            }
+
+            if self.starts_with(">")
+            {
+                self.skip(">");
+
+                break false;
+            }
+
+            if !has_space
+            {
+                return Err(self.unexpected(&inside));
+            }
+
+            let attribute = self.parse_attribute(&inside)?;
+
+            if attributes.iter().any(|other| other.name == attribute.name)
+            {
+                return Err(XmlError::new(XmlErrorKind::DuplicateAttribute, attribute.name, attribute.tag.position()));
+            }
+
+            attributes.push(attribute);
+        };
+
+        let bindings = self.bindings.len();
+        self.bind(&attributes)?;
+
+        let namespace_uri = match name.split_once(':')
+        {
+            Some((prefix, _)) => self.lookup(prefix).ok_or_else(||
+            {
+                XmlError::new(XmlErrorKind::UnboundPrefix, prefix, start)
+            })?,
+            None => self.lookup("").unwrap_or_default()
+        };
+
+        self.resolve_prefixes(&mut attributes)?;
+
+        let element = OpenElement{
+            name,
+            namespace_uri,
+            start,
+            tags: attributes.into_iter().map(|attribute| attribute.tag).collect(),
+            children: Vec::new(),
+            bindings
+        };
+
+        Ok(if is_empty
+        {
+            Started::Closed(self.close(element))
+        } else
+        {
+            Started::Open(element)
+        })
+    }
+
+    fn parse_attribute(&mut self, inside: &str) -> Result<Attribute<'a>, XmlError>
+    {
+        let start = self.position;
+        let name = self.parse_name()?;
+
+        if !is_qualified_name(name)
+        {
+            return Err(XmlError::new(XmlErrorKind::InvalidName, name, start));
+        }
+
+        // unlike html every tag needs a quoted value
+        self.skip_whitespace();
+        self.expect("=", inside)?;
+        self.skip_whitespace();
+
+        let quote = match self.peek()
+        {
+            Some('"') => Quote::Double,
+            Some('\'') => Quote::Single,
+            _ => return Err(self.unexpected(inside))
+        };
+
+        let value_start = self.position;
+        self.advance();
+
+        let delimiter = quote.delimiter().expect("quoted values have a delimiter");
+
+        let rest = self.rest();
+        let raw = match rest.find(delimiter)
+        {
+            Some(length) => &rest[..length],
+            None => return Err(XmlError::new(XmlErrorKind::UnexpectedEnd, inside, value_start))
+        };
+
+        if let Some(index) = raw.find('<')
+        {
+            let position = self.position.advanced(&raw[..index]);
+
+            return Err(XmlError::new(XmlErrorKind::UnexpectedChar, "'<' in an attribute value", position));
+        }
+
+        let decoded = self.decode(raw, true)?;
+        self.advance();
+
+        let value = match &decoded
+        {
+            Some(decoded) => Cow::Owned(decoded.clone()),
+            None => Cow::Borrowed(raw)
+        };
+
+        let content = Text::from_parts(raw, decoded, Span::new(value_start, self.position));
+        let tag = Tag::from_parts(name, Span::new(start, self.position), content, quote);
+
+        Ok(Attribute{name, value, tag})
+    }
+
+    fn parse_end(&mut self, element: Option<OpenElement<'a>>) -> Result<Element<'a>, XmlError>
+    {
+        let start = self.position;
+
+        self.skip("</");
+
+        let name = self.parse_name()?;
+
+        self.skip_whitespace();
+        self.expect(">", &format!("the {name} end tag"))?;
+
+        match element
+        {
+            Some(element) if element.name == name => Ok(self.close(element)),
+            _ => Err(XmlError::new(XmlErrorKind::MismatchedEnd, format!("</{name}>"), start))
+        }
+    }
+
+    fn close(&mut self, element: OpenElement<'a>) -> Element<'a>
+    {
+        self.bindings.truncate(element.bindings);
+
+        element.close(self.position)
+    }
+
+    // xmlns tags declare prefixes for the element theyre on and everything inside of it
+    fn bind(&mut self, attributes: &[Attribute<'a>]) -> Result<(), XmlError>
+    {
+        for attribute in attributes
+        {
+            let prefix = match attribute.name.split_once(':')
+            {
+                None if attribute.name == "xmlns" => "",
+                Some(("xmlns", local)) => local,
+                _ => continue
+            };
+
+            let uri = &attribute.value;
+
+            let is_xml = uri == Namespace::Xml.uri();
+            let is_valid = match prefix
+            {
+                "xml" => is_xml,
+                "xmlns" => false,
+                // only the default namespace can be unset with an empty uri
+                _ => !is_xml && uri != Namespace::Xmlns.uri() && (prefix.is_empty() || !uri.is_empty())
+            };
+
+            if !is_valid
+            {
+                return Err(XmlError::new(XmlErrorKind::ReservedPrefix, attribute.name, attribute.tag.position()));
+            }
+
+            self.bindings.push((prefix, uri.clone()));
+        }
+
+        Ok(())
+    }
+
+    fn lookup(&self, prefix: &str) -> Option<Cow<'a, str>>
+    {
+        if prefix == "xml"
+        {
+            return Some(Cow::Borrowed(Namespace::Xml.uri()));
+        }
+
+        self.bindings.iter().rev().find(|(bound, _)| *bound == prefix).map(|(_, uri)| uri.clone())
+    }
+
+    // prefixed tags need their prefix declared, and two of them cant end up with the same name,
+    // the uri the prefix resolves to is kept on the tag
+    fn resolve_prefixes(&self, attributes: &mut [Attribute<'a>]) -> Result<(), XmlError>
+    {
+        let mut expanded: Vec<(Cow<'a, str>, &str)> = Vec::new();
+
+        for attribute in attributes
+        {
+            let (prefix, local) = match attribute.name.split_once(':')
+            {
+                Some(("xmlns", _)) | None => continue,
+                Some(parts) => parts
+            };
+
+            let position = attribute.tag.position();
+
+            let uri = self.lookup(prefix).filter(|uri| !uri.is_empty()).ok_or_else(||
+            {
+                XmlError::new(XmlErrorKind::UnboundPrefix, prefix, position)
+            })?;
+
+            if expanded.iter().any(|(other, other_local)| *other == uri && *other_local == local)
+            {
+                return Err(XmlError::new(XmlErrorKind::DuplicateAttribute, attribute.name, position));
+            }
+
+            attribute.tag.set_namespace_uri(uri.clone());
+
+            expanded.push((uri, local));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,932 @@
+use std::{
+    cmp::Ordering,
+    str::FromStr
+};
+
+use super::{
+    Child,
+    Element,
+    Document,
+    Tag,
+    Text,
+    ProcessingInstruction,
+    XPathError,
+    XPathErrorKind
+};
+
+mod parse;
+mod functions;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis
+{
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfNode
+}
+
+impl Axis
+{
+    fn from_name(name: &str) -> Option<Self>
+    {
+        Some(match name
+        {
+            "ancestor" => Self::Ancestor,
+            "ancestor-or-self" => Self::AncestorOrSelf,
+            "attribute" => Self::Attribute,
+            "child" => Self::Child,
+            "descendant" => Self::Descendant,
+            "descendant-or-self" => Self::DescendantOrSelf,
+            "following" => Self::Following,
+            "following-sibling" => Self::FollowingSibling,
+            "namespace" => Self::Namespace,
+            "parent" => Self::Parent,
+            "preceding" => Self::Preceding,
+            "preceding-sibling" => Self::PrecedingSibling,
+            "self" => Self::SelfNode,
+            _ => return None
+        })
+    }
+
+    // positions in predicates count backwards from the context node on these
+    pub fn is_reverse(&self) -> bool
+    {
+        matches!(self, Self::Ancestor | Self::AncestorOrSelf | Self::Preceding | Self::PrecedingSibling)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeTest
+{
+    // *
+    Any,
+    // prefixes match how the document wrote them, theres nothing to bind them to namespaces
+    Name(String),
+    // prefix:*
+    Prefix(String),
+    // node()
+    Node,
+    // text()
+    Text,
+    // comment()
+    Comment,
+    // processing-instruction(), only xml documents have any
+    ProcessingInstruction(Option<String>)
+}
+
+impl NodeTest
+{
+    fn from_type(name: &str) -> Option<Self>
+    {
+        Some(match name
+        {
+            "node" => Self::Node,
+            "text" => Self::Text,
+            "comment" => Self::Comment,
+            "processing-instruction" => Self::ProcessingInstruction(None),
+            _ => return None
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step
+{
+    pub axis: Axis,
+    pub test: NodeTest,
+    pub predicates: Vec<Expr>
+}
+
+impl Step
+{
+    // what // stands for
+    fn descendant_or_self() -> Self
+    {
+        Self{axis: Axis::DescendantOrSelf, test: NodeTest::Node, predicates: Vec::new()}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator
+{
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Union
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStart
+{
+    Context,
+    Root,
+    Filter(Box<Expr>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr
+{
+    Binary(Box<Expr>, Operator, Box<Expr>),
+    Negate(Box<Expr>),
+    Literal(String),
+    Number(f64),
+    Function(String, Vec<Expr>),
+    Filter(Box<Expr>, Vec<Expr>),
+    Path(PathStart, Vec<Step>)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum XPathNode<'b, 'a>
+{
+    // the children of the document, when evaluating from an element its empty
+    Root(&'b [Child<'a>]),
+    Element(&'b Element<'a>),
+    Text(&'b Text<'a>),
+    Comment(&'b str),
+    ProcessingInstruction(&'b ProcessingInstruction<'a>),
+    Attribute(&'b Tag<'a>)
+}
+
+impl<'b, 'a> XPathNode<'b, 'a>
+{
+    pub fn element(&self) -> Option<&'b Element<'a>>
+    {
+        match self
+        {
+            Self::Element(element) => Some(element),
+            _ => None
+        }
+    }
+
+    pub fn attribute(&self) -> Option<&'b Tag<'a>>
+    {
+        match self
+        {
+            Self::Attribute(tag) => Some(tag),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> Option<&'b str>
+    {
+        match self
+        {
+            Self::Element(element) => Some(element.name()),
+            Self::Attribute(tag) => Some(tag.name()),
+            Self::ProcessingInstruction(instruction) => Some(instruction.target()),
+            _ => None
+        }
+    }
+
+    // all the text inside of it for elements, the value for attributes
+    pub fn string_value(&self) -> String
+    {
+        fn collect(out: &mut String, children: &[Child<'_>])
+        {
+            for child in children
+            {
+                match child
+                {
+                    Child::Element(element) => collect(out, element.children()),
+                    Child::Text(text) => out.push_str(text.content()),
+                    Child::Comment(_) | Child::ProcessingInstruction(_) => ()
+                }
+            }
+        }
+
+        match self
+        {
+            Self::Root(children) =>
+            {
+                let mut out = String::new();
+                collect(&mut out, children);
+
+                out
+            },
+            Self::Element(element) =>
+            {
+                let mut out = String::new();
+                collect(&mut out, element.children());
+
+                out
+            },
+            Self::Text(text) => text.content().to_owned(),
+            Self::Comment(comment) => (*comment).to_owned(),
+            Self::ProcessingInstruction(instruction) => instruction.data().to_owned(),
+            Self::Attribute(tag) => tag.content().unwrap_or_default().to_owned()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum XPathValue<'b, 'a>
+{
+    // in document order
+    Nodes(Vec<XPathNode<'b, 'a>>),
+    String(String),
+    Number(f64),
+    Boolean(bool)
+}
+
+impl<'b, 'a> XPathValue<'b, 'a>
+{
+    pub fn nodes(&self) -> Option<&[XPathNode<'b, 'a>]>
+    {
+        match self
+        {
+            Self::Nodes(nodes) => Some(nodes),
+            _ => None
+        }
+    }
+
+    // the elements in a node-set, anything else is empty
+    pub fn elements(&self) -> Vec<&'b Element<'a>>
+    {
+        self.nodes().unwrap_or_default().iter().filter_map(|node| node.element()).collect()
+    }
+
+    // these convert like the string, number and boolean functions do
+    pub fn string(&self) -> String
+    {
+        match self
+        {
+            Self::Nodes(nodes) => nodes.first().map(|node| node.string_value()).unwrap_or_default(),
+            Self::String(x) => x.clone(),
+            Self::Number(x) => number_to_string(*x),
+            Self::Boolean(x) => x.to_string()
+        }
+    }
+
+    pub fn number(&self) -> f64
+    {
+        match self
+        {
+            Self::Number(x) => *x,
+            Self::Boolean(x) => if *x { 1.0 } else { 0.0 },
+            _ => string_to_number(&self.string())
+        }
+    }
+
+    pub fn boolean(&self) -> bool
+    {
+        match self
+        {
+            Self::Nodes(nodes) => !nodes.is_empty(),
+            Self::String(x) => !x.is_empty(),
+            Self::Number(x) => *x != 0.0 && !x.is_nan(),
+            Self::Boolean(x) => *x
+        }
+    }
+}
+
+fn is_xml_whitespace(c: char) -> bool
+{
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+fn number_to_string(number: f64) -> String
+{
+    if number.is_nan()
+    {
+        "NaN".to_owned()
+    } else if number.is_infinite()
+    {
+        if number > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else if number == 0.0
+    {
+        // negative zero too
+        "0".to_owned()
+    } else
+    {
+        number.to_string()
+    }
+}
+
+// only plain decimals count as numbers, no exponents or infinities
+fn string_to_number(text: &str) -> f64
+{
+    let text = text.trim_matches(is_xml_whitespace);
+
+    let digits = text.strip_prefix('-').unwrap_or(text);
+
+    let is_number = digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.chars().filter(|c| *c == '.').count() <= 1
+        && digits.chars().any(|c| c.is_ascii_digit());
+
+    if is_number
+    {
+        text.parse().unwrap_or(f64::NAN)
+    } else
+    {
+        f64::NAN
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value
+{
+    // ids in the tree, sorted so theyre in document order
+    Nodes(Vec<usize>),
+    String(String),
+    Number(f64),
+    Boolean(bool)
+}
+
+struct Entry<'b, 'a>
+{
+    node: XPathNode<'b, 'a>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    attributes: Vec<usize>,
+    // where it is in its parents children
+    index: usize,
+    // the last id inside of it, ids r given out in document order so everything between is a descendant
+    end: usize
+}
+
+// the parsed tree flattened so its easy to go up and sideways in it
+struct Tree<'b, 'a>
+{
+    entries: Vec<Entry<'b, 'a>>
+}
+
+impl<'b, 'a> Tree<'b, 'a>
+{
+    fn from_children(root: XPathNode<'b, 'a>, children: impl Iterator<Item=XPathNode<'b, 'a>>) -> Self
+    {
+        let mut tree = Self{entries: Vec::new()};
+
+        let id = tree.push(root, None, 0);
+        for (index, child) in children.enumerate()
+        {
+            let child = tree.push_node(child, id, index);
+            tree.entries[id].children.push(child);
+        }
+
+        tree.entries[id].end = tree.entries.len() - 1;
+
+        tree
+    }
+
+    fn from_element(element: &'b Element<'a>) -> Self
+    {
+        Self::from_children(XPathNode::Root(&[]), [XPathNode::Element(element)].into_iter())
+    }
+
+    fn from_document(document: &'b Document<'a>) -> Self
+    {
+        let children = document.children();
+
+        Self::from_children(XPathNode::Root(children), children.iter().map(Self::child_node))
+    }
+
+    fn child_node(child: &'b Child<'a>) -> XPathNode<'b, 'a>
+    {
+        match child
+        {
+            Child::Element(element) => XPathNode::Element(element),
+            Child::Text(text) => XPathNode::Text(text),
+            Child::Comment(comment) => XPathNode::Comment(comment.text()),
+            Child::ProcessingInstruction(instruction) => XPathNode::ProcessingInstruction(instruction)
+        }
+    }
+
+    fn push(&mut self, node: XPathNode<'b, 'a>, parent: Option<usize>, index: usize) -> usize
+    {
+        let id = self.entries.len();
+
+        self.entries.push(Entry{node, parent, children: Vec::new(), attributes: Vec::new(), index, end: id});
+
+        id
+    }
+
+    fn push_node(&mut self, node: XPathNode<'b, 'a>, parent: usize, index: usize) -> usize
+    {
+        let id = self.push(node, Some(parent), index);
+
+        if let XPathNode::Element(element) = node
+        {
+            for (index, tag) in element.tags().iter().enumerate()
+            {
+                let attribute = self.push(XPathNode::Attribute(tag), Some(id), index);
+                self.entries[id].attributes.push(attribute);
+            }
+
+            for (index, child) in element.children().iter().enumerate()
+            {
+                let child = self.push_node(Self::child_node(child), id, index);
+                self.entries[id].children.push(child);
+            }
+
+            self.entries[id].end = self.entries.len() - 1;
+        }
+
+        id
+    }
+
+    fn node(&self, id: usize) -> XPathNode<'b, 'a>
+    {
+        self.entries[id].node
+    }
+
+    fn is_attribute(&self, id: usize) -> bool
+    {
+        matches!(self.node(id), XPathNode::Attribute(_))
+    }
+
+    fn string_value(&self, id: usize) -> String
+    {
+        match self.node(id)
+        {
+            // the root of an element evaluation doesnt know about the element
+            XPathNode::Root(_) =>
+            {
+                self.entries[id].children.iter().map(|child| self.string_value(*child)).collect()
+            },
+            node => node.string_value()
+        }
+    }
+
+    fn ancestors(&self, id: usize) -> impl Iterator<Item=usize> + '_
+    {
+        std::iter::successors(self.entries[id].parent, |id| self.entries[*id].parent)
+    }
+
+    fn siblings(&self, id: usize) -> &[usize]
+    {
+        if self.is_attribute(id)
+        {
+            return &[];
+        }
+
+        match self.entries[id].parent
+        {
+            Some(parent) => &self.entries[parent].children,
+            None => &[]
+        }
+    }
+
+    // in the order of the axis, so reverse axes go backwards from the node
+    fn axis(&self, axis: Axis, id: usize) -> Vec<usize>
+    {
+        let entry = &self.entries[id];
+
+        match axis
+        {
+            Axis::Child => entry.children.clone(),
+            Axis::Attribute => entry.attributes.clone(),
+            Axis::SelfNode => vec![id],
+            Axis::Parent => entry.parent.into_iter().collect(),
+            Axis::Ancestor => self.ancestors(id).collect(),
+            Axis::AncestorOrSelf => std::iter::once(id).chain(self.ancestors(id)).collect(),
+            Axis::Descendant => (id + 1..=entry.end).filter(|id| !self.is_attribute(*id)).collect(),
+            Axis::DescendantOrSelf =>
+            {
+                std::iter::once(id).chain((id + 1..=entry.end).filter(|id| !self.is_attribute(*id))).collect()
+            },
+            Axis::FollowingSibling => self.siblings(id).get(entry.index + 1..).unwrap_or_default().to_vec(),
+            Axis::PrecedingSibling =>
+            {
+                let siblings = self.siblings(id);
+
+                siblings[..entry.index.min(siblings.len())].iter().rev().copied().collect()
+            },
+            Axis::Following => (entry.end + 1..self.entries.len()).filter(|id| !self.is_attribute(*id)).collect(),
+            Axis::Preceding =>
+            {
+                let ancestors = self.ancestors(id).collect::<Vec<_>>();
+
+                (0..id).rev().filter(|id| !self.is_attribute(*id) && !ancestors.contains(id)).collect()
+            },
+            Axis::Namespace => Vec::new()
+        }
+    }
+
+    fn matches(&self, axis: Axis, test: &NodeTest, id: usize) -> bool
+    {
+        let node = self.node(id);
+
+        // what * and names match depends on the axis
+        let is_principal = match node
+        {
+            XPathNode::Attribute(_) => axis == Axis::Attribute,
+            XPathNode::Element(_) => axis != Axis::Attribute,
+            _ => false
+        };
+
+        match test
+        {
+            NodeTest::Any => is_principal,
+            NodeTest::Name(name) => is_principal && match node
+            {
+                XPathNode::Element(element) => element.is_named(name),
+                _ => node.name().is_some_and(|x| x.eq_ignore_ascii_case(name))
+            },
+            NodeTest::Prefix(prefix) =>
+            {
+                let found = match node
+                {
+                    XPathNode::Element(element) => element.prefix(),
+                    XPathNode::Attribute(tag) => tag.prefix(),
+                    _ => None
+                };
+
+                is_principal && found == Some(prefix.as_str())
+            },
+            NodeTest::Node => true,
+            NodeTest::Text => matches!(node, XPathNode::Text(_)),
+            NodeTest::Comment => matches!(node, XPathNode::Comment(_)),
+            NodeTest::ProcessingInstruction(target) =>
+            {
+                match node
+                {
+                    XPathNode::ProcessingInstruction(instruction) =>
+                    {
+                        target.as_ref().is_none_or(|target| target == instruction.target())
+                    },
+                    _ => false
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Context
+{
+    node: usize,
+    // counted from 1
+    position: usize,
+    size: usize
+}
+
+struct Evaluator<'t, 'b, 'a>
+{
+    tree: &'t Tree<'b, 'a>
+}
+
+impl<'t, 'b, 'a> Evaluator<'t, 'b, 'a>
+{
+    fn string(&self, value: &Value) -> String
+    {
+        match value
+        {
+            Value::Nodes(nodes) => nodes.first().map(|id| self.tree.string_value(*id)).unwrap_or_default(),
+            Value::String(x) => x.clone(),
+            Value::Number(x) => number_to_string(*x),
+            Value::Boolean(x) => x.to_string()
+        }
+    }
+
+    fn number(&self, value: &Value) -> f64
+    {
+        match value
+        {
+            Value::Number(x) => *x,
+            Value::Boolean(x) => if *x { 1.0 } else { 0.0 },
+            _ => string_to_number(&self.string(value))
+        }
+    }
+
+    fn boolean(&self, value: &Value) -> bool
+    {
+        match value
+        {
+            Value::Nodes(nodes) => !nodes.is_empty(),
+            Value::String(x) => !x.is_empty(),
+            Value::Number(x) => *x != 0.0 && !x.is_nan(),
+            Value::Boolean(x) => *x
+        }
+    }
+
+    fn nodes(&self, value: Value) -> Result<Vec<usize>, XPathError>
+    {
+        let found = match value
+        {
+            Value::Nodes(nodes) => return Ok(nodes),
+            Value::String(x) => format!("string \"{x}\""),
+            Value::Number(x) => format!("number {}", number_to_string(x)),
+            Value::Boolean(x) => format!("boolean {x}")
+        };
+
+        Err(XPathError::new(XPathErrorKind::NotANodeSet, found, None))
+    }
+
+    fn evaluate(&self, expr: &Expr, context: Context) -> Result<Value, XPathError>
+    {
+        Ok(match expr
+        {
+            Expr::Literal(x) => Value::String(x.clone()),
+            Expr::Number(x) => Value::Number(*x),
+            Expr::Negate(x) => Value::Number(-self.number(&self.evaluate(x, context)?)),
+            Expr::Function(name, arguments) => functions::call(self, name, arguments, context)?,
+            Expr::Binary(a, operator, b) => self.binary(a, *operator, b, context)?,
+            Expr::Filter(primary, predicates) =>
+            {
+                let mut nodes = self.nodes(self.evaluate(primary, context)?)?;
+
+                for predicate in predicates
+                {
+                    nodes = self.filter(nodes, predicate)?;
+                }
+
+                Value::Nodes(nodes)
+            },
+            Expr::Path(start, steps) =>
+            {
+                let start = match start
+                {
+                    PathStart::Context => vec![context.node],
+                    PathStart::Root => vec![0],
+                    PathStart::Filter(filter) => self.nodes(self.evaluate(filter, context)?)?
+                };
+
+                Value::Nodes(self.steps(start, steps)?)
+            }
+        })
+    }
+
+    fn steps(&self, mut nodes: Vec<usize>, steps: &[Step]) -> Result<Vec<usize>, XPathError>
+    {
+        for step in steps
+        {
+            let mut next = Vec::new();
+
+            for node in nodes
+            {
+                let mut selected = self.tree.axis(step.axis, node);
+                selected.retain(|id| self.tree.matches(step.axis, &step.test, *id));
+
+                for predicate in &step.predicates
+                {
+                    selected = self.filter(selected, predicate)?;
+                }
+
+                next.extend(selected);
+            }
+
+            next.sort_unstable();
+            next.dedup();
+
+            nodes = next;
+        }
+
+        Ok(nodes)
+    }
+
+    // numbers r compared against the position, anything else is converted to a boolean
+    fn filter(&self, nodes: Vec<usize>, predicate: &Expr) -> Result<Vec<usize>, XPathError>
+    {
+        let size = nodes.len();
+
+        let mut kept = Vec::new();
+        for (index, node) in nodes.into_iter().enumerate()
+        {
+            let position = index + 1;
+
+            let is_kept = match self.evaluate(predicate, Context{node, position, size})?
+            {
+                Value::Number(x) => x == position as f64,
+                value => self.boolean(&value)
+            };
+
+            if is_kept
+            {
+                kept.push(node);
+            }
+        }
+
+        Ok(kept)
+    }
+
+    fn binary(&self, a: &Expr, operator: Operator, b: &Expr, context: Context) -> Result<Value, XPathError>
+    {
+        match operator
+        {
+            Operator::Or =>
+            {
+                let value = self.boolean(&self.evaluate(a, context)?) || self.boolean(&self.evaluate(b, context)?);
+
+                return Ok(Value::Boolean(value));
+            },
+            Operator::And =>
+            {
+                let value = self.boolean(&self.evaluate(a, context)?) && self.boolean(&self.evaluate(b, context)?);
+
+                return Ok(Value::Boolean(value));
+            },
+            _ => ()
+        }
+
+        let (a_value, b_value) = (self.evaluate(a, context)?, self.evaluate(b, context)?);
+
+        let number = |f: fn(f64, f64) -> f64|
+        {
+            Value::Number(f(self.number(&a_value), self.number(&b_value)))
+        };
+
+        Ok(match operator
+        {
+            Operator::Add => number(|a, b| a + b),
+            Operator::Subtract => number(|a, b| a - b),
+            Operator::Multiply => number(|a, b| a * b),
+            Operator::Divide => number(|a, b| a / b),
+            // truncating like rusts % does, which is what xpath wants
+            Operator::Modulo => number(|a, b| a % b),
+            Operator::Union =>
+            {
+                let mut nodes = self.nodes(a_value)?;
+                nodes.extend(self.nodes(b_value)?);
+
+                nodes.sort_unstable();
+                nodes.dedup();
+
+                Value::Nodes(nodes)
+            },
+            _ => Value::Boolean(self.compare(&a_value, operator, &b_value))
+        })
+    }
+
+    // node-sets compare true if any of their nodes do
+    fn compare(&self, a: &Value, operator: Operator, b: &Value) -> bool
+    {
+        match (a, b)
+        {
+            (Value::Nodes(a), Value::Nodes(b)) =>
+            {
+                let b = b.iter().map(|id| Value::String(self.tree.string_value(*id))).collect::<Vec<_>>();
+
+                a.iter().any(|id|
+                {
+                    let a = Value::String(self.tree.string_value(*id));
+
+                    b.iter().any(|b| self.compare_values(&a, operator, b))
+                })
+            },
+            (Value::Nodes(nodes), other) | (other, Value::Nodes(nodes)) =>
+            {
+                let is_flipped = matches!(b, Value::Nodes(_));
+
+                if let Value::Boolean(_) = other
+                {
+                    let nodes = Value::Boolean(!nodes.is_empty());
+
+                    return if is_flipped
+                    {
+                        self.compare_values(other, operator, &nodes)
+                    } else
+                    {
+                        self.compare_values(&nodes, operator, other)
+                    };
+                }
+
+                nodes.iter().any(|id|
+                {
+                    let node = match other
+                    {
+                        Value::Number(_) => Value::Number(string_to_number(&self.tree.string_value(*id))),
+                        _ => Value::String(self.tree.string_value(*id))
+                    };
+
+                    if is_flipped
+                    {
+                        self.compare_values(other, operator, &node)
+                    } else
+                    {
+                        self.compare_values(&node, operator, other)
+                    }
+                })
+            },
+            _ => self.compare_values(a, operator, b)
+        }
+    }
+
+    fn compare_values(&self, a: &Value, operator: Operator, b: &Value) -> bool
+    {
+        if matches!(operator, Operator::Equal | Operator::NotEqual)
+        {
+            let is_equal = match (a, b)
+            {
+                (Value::Boolean(_), _) | (_, Value::Boolean(_)) => self.boolean(a) == self.boolean(b),
+                (Value::Number(_), _) | (_, Value::Number(_)) => self.number(a) == self.number(b),
+                _ => self.string(a) == self.string(b)
+            };
+
+            return is_equal == (operator == Operator::Equal);
+        }
+
+        // nan compares false to everything
+        let ordering = match self.number(a).partial_cmp(&self.number(b))
+        {
+            Some(x) => x,
+            None => return false
+        };
+
+        match operator
+        {
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterEqual => ordering != Ordering::Less,
+            _ => unreachable!()
+        }
+    }
+
+    fn public_value(&self, value: Value) -> XPathValue<'b, 'a>
+    {
+        match value
+        {
+            Value::Nodes(nodes) => XPathValue::Nodes(nodes.into_iter().map(|id| self.tree.node(id)).collect()),
+            Value::String(x) => XPathValue::String(x),
+            Value::Number(x) => XPathValue::Number(x),
+            Value::Boolean(x) => XPathValue::Boolean(x)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPath
+{
+    expr: Expr
+}
+
+impl XPath
+{
+    pub fn parse(text: &str) -> Result<Self, XPathError>
+    {
+        parse::parse_expression(text).map(|expr| Self{expr})
+    }
+
+    pub fn expression(&self) -> &Expr
+    {
+        &self.expr
+    }
+
+    // the element is the context node, its parent is an empty root so / and // still work
+    pub fn evaluate<'b, 'a>(&self, element: &'b Element<'a>) -> Result<XPathValue<'b, 'a>, XPathError>
+    {
+        self.evaluate_tree(&Tree::from_element(element), 1)
+    }
+
+    // the context node is the document itself
+    pub fn evaluate_document<'b, 'a>(&self, document: &'b Document<'a>) -> Result<XPathValue<'b, 'a>, XPathError>
+    {
+        self.evaluate_tree(&Tree::from_document(document), 0)
+    }
+
+    fn evaluate_tree<'b, 'a>(&self, tree: &Tree<'b, 'a>, node: usize) -> Result<XPathValue<'b, 'a>, XPathError>
+    {
+        let evaluator = Evaluator{tree};
+
+        let value = evaluator.evaluate(&self.expr, Context{node, position: 1, size: 1})?;
+
+        Ok(evaluator.public_value(value))
+    }
+}
+
+impl FromStr for XPath
+{
+    type Err = XPathError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err>
+    {
+        Self::parse(text)
+    }
+}
+
+impl<'a> Element<'a>
+{
+    pub fn xpath(&self, expression: &str) -> Result<XPathValue<'_, 'a>, XPathError>
+    {
+        XPath::parse(expression)?.evaluate(self)
+    }
+}
+
+impl<'a> Document<'a>
+{
+    pub fn xpath(&self, expression: &str) -> Result<XPathValue<'_, 'a>, XPathError>
+    {
+        XPath::parse(expression)?.evaluate_document(self)
+    }
+}
@@ -0,0 +1,248 @@
+use super::{
+    Expr,
+    Value,
+    Context,
+    Evaluator,
+    XPathNode,
+    is_xml_whitespace,
+    string_to_number
+};
+
+use crate::parser::XPathError;
+
+
+// the least and most arguments each function takes
+pub fn arity(name: &str) -> Option<(usize, usize)>
+{
+    Some(match name
+    {
+        "last" | "position" | "true" | "false" => (0, 0),
+        "count" | "id" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round" => (1, 1),
+        "local-name" | "namespace-uri" | "name" | "string" | "string-length" | "normalize-space" | "number" => (0, 1),
+        "starts-with" | "contains" | "substring-before" | "substring-after" => (2, 2),
+        "substring" => (2, 3),
+        "translate" => (3, 3),
+        "concat" => (2, usize::MAX),
+        _ => return None
+    })
+}
+
+fn round(x: f64) -> f64
+{
+    if x.is_nan() || x.is_infinite()
+    {
+        return x;
+    }
+
+    // halves go up, even negative ones
+    let rounded = (x + 0.5).floor();
+
+    if rounded == 0.0 && x < 0.0
+    {
+        -0.0
+    } else
+    {
+        rounded
+    }
+}
+
+fn substring(text: &str, start: f64, length: Option<f64>) -> String
+{
+    let start = round(start);
+    let end = length.map(|length| start + round(length)).unwrap_or(f64::INFINITY);
+
+    text.chars().enumerate().filter(|(index, _)|
+    {
+        let position = (index + 1) as f64;
+
+        position >= start && position < end
+    }).map(|(_, c)| c).collect()
+}
+
+fn translate(text: &str, from: &str, to: &str) -> String
+{
+    let to = to.chars().collect::<Vec<_>>();
+
+    text.chars().filter_map(|c|
+    {
+        match from.chars().position(|x| x == c)
+        {
+            Some(index) => to.get(index).copied(),
+            None => Some(c)
+        }
+    }).collect()
+}
+
+fn normalize_space(text: &str) -> String
+{
+    text.split(is_xml_whitespace).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+pub fn call(
+    evaluator: &Evaluator<'_, '_, '_>,
+    name: &str,
+    arguments: &[Expr],
+    context: Context
+) -> Result<Value, XPathError>
+{
+    let tree = evaluator.tree;
+
+    let argument = |index: usize| evaluator.evaluate(&arguments[index], context);
+
+    let string = |index: usize| -> Result<String, XPathError>
+    {
+        Ok(evaluator.string(&argument(index)?))
+    };
+
+    let number = |index: usize| -> Result<f64, XPathError>
+    {
+        Ok(evaluator.number(&argument(index)?))
+    };
+
+    let nodes = |index: usize| -> Result<Vec<usize>, XPathError>
+    {
+        evaluator.nodes(argument(index)?)
+    };
+
+    // the functions that default to the context node
+    let first_node = || -> Result<Option<usize>, XPathError>
+    {
+        if arguments.is_empty()
+        {
+            Ok(Some(context.node))
+        } else
+        {
+            Ok(nodes(0)?.first().copied())
+        }
+    };
+
+    let string_or_context = || -> Result<String, XPathError>
+    {
+        if arguments.is_empty()
+        {
+            Ok(tree.string_value(context.node))
+        } else
+        {
+            string(0)
+        }
+    };
+
+    Ok(match name
+    {
+        "last" => Value::Number(context.size as f64),
+        "position" => Value::Number(context.position as f64),
+        "count" => Value::Number(nodes(0)?.len() as f64),
+        "id" =>
+        {
+            let ids = match argument(0)?
+            {
+                Value::Nodes(nodes) => nodes.iter().map(|id| tree.string_value(*id)).collect::<Vec<_>>().join(" "),
+                value => evaluator.string(&value)
+            };
+
+            let ids = ids.split(is_xml_whitespace).filter(|id| !id.is_empty()).collect::<Vec<_>>();
+
+            let found = (0..tree.entries.len()).filter(|id|
+            {
+                tree.node(*id).element().and_then(|element| element.id()).is_some_and(|value| ids.contains(&value))
+            }).collect();
+
+            Value::Nodes(found)
+        },
+        "local-name" | "name" | "namespace-uri" =>
+        {
+            let node = first_node()?.map(|id| tree.node(id));
+            let found = node.and_then(|node| node.name()).unwrap_or_default();
+
+            Value::String(match name
+            {
+                "namespace-uri" =>
+                {
+                    let uri = match node
+                    {
+                        Some(XPathNode::Element(element)) => Some(element.namespace_uri()),
+                        Some(XPathNode::Attribute(tag)) => tag.namespace_uri(),
+                        _ => None
+                    };
+
+                    uri.unwrap_or_default().to_owned()
+                },
+                "local-name" => found.rsplit(':').next().unwrap_or_default().to_owned(),
+                _ => found.to_owned()
+            })
+        },
+        "string" => Value::String(string_or_context()?),
+        "concat" =>
+        {
+            let mut out = String::new();
+            for index in 0..arguments.len()
+            {
+                out.push_str(&string(index)?);
+            }
+
+            Value::String(out)
+        },
+        "starts-with" => Value::Boolean(string(0)?.starts_with(&string(1)?)),
+        "contains" => Value::Boolean(string(0)?.contains(&string(1)?)),
+        "substring-before" =>
+        {
+            let text = string(0)?;
+
+            Value::String(text.split_once(&string(1)?).map(|(before, _)| before.to_owned()).unwrap_or_default())
+        },
+        "substring-after" =>
+        {
+            let text = string(0)?;
+
+            Value::String(text.split_once(&string(1)?).map(|(_, after)| after.to_owned()).unwrap_or_default())
+        },
+        "substring" =>
+        {
+            let length = if arguments.len() > 2 { Some(number(2)?) } else { None };
+
+            Value::String(substring(&string(0)?, number(1)?, length))
+        },
+        "string-length" => Value::Number(string_or_context()?.chars().count() as f64),
+        "normalize-space" => Value::String(normalize_space(&string_or_context()?)),
+        "translate" => Value::String(translate(&string(0)?, &string(1)?, &string(2)?)),
+        "boolean" => Value::Boolean(evaluator.boolean(&argument(0)?)),
+        "not" => Value::Boolean(!evaluator.boolean(&argument(0)?)),
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        "lang" =>
+        {
+            let expected = string(0)?.to_ascii_lowercase();
+
+            let lang = std::iter::once(context.node).chain(tree.ancestors(context.node)).find_map(|id|
+            {
+                let element = tree.node(id).element()?;
+
+                element.attr("lang").or_else(|| element.attr("xml:lang")).map(str::to_ascii_lowercase)
+            });
+
+            Value::Boolean(lang.is_some_and(|lang|
+            {
+                lang.strip_prefix(&expected).is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+            }))
+        },
+        "number" =>
+        {
+            if arguments.is_empty()
+            {
+                Value::Number(string_to_number(&tree.string_value(context.node)))
+            } else
+            {
+                Value::Number(number(0)?)
+            }
+        },
+        "sum" =>
+        {
+            Value::Number(nodes(0)?.iter().map(|id| string_to_number(&tree.string_value(*id))).sum())
+        },
+        "floor" => Value::Number(number(0)?.floor()),
+        "ceiling" => Value::Number(number(0)?.ceil()),
+        "round" => Value::Number(round(number(0)?)),
+        _ => unreachable!("arity checks the names when parsing")
+    })
+}
+
@@ -0,0 +1,661 @@
+use super::{
+    Axis,
+    NodeTest,
+    Step,
+    Operator,
+    PathStart,
+    Expr,
+    functions
+};
+
+use crate::parser::{
+    XPathError,
+    XPathErrorKind
+};
+
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token
+{
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Star,
+    Literal(String),
+    Number(f64),
+    // prefixed names keep their prefix, prefix:* ends with the star
+    Name(String),
+    Variable(String)
+}
+
+impl Token
+{
+    fn describe(&self) -> String
+    {
+        match self
+        {
+            Self::LeftParen => "(".to_owned(),
+            Self::RightParen => ")".to_owned(),
+            Self::LeftBracket => "[".to_owned(),
+            Self::RightBracket => "]".to_owned(),
+            Self::Dot => ".".to_owned(),
+            Self::DotDot => "..".to_owned(),
+            Self::At => "@".to_owned(),
+            Self::Comma => ",".to_owned(),
+            Self::ColonColon => "::".to_owned(),
+            Self::Slash => "/".to_owned(),
+            Self::DoubleSlash => "//".to_owned(),
+            Self::Pipe => "|".to_owned(),
+            Self::Plus => "+".to_owned(),
+            Self::Minus => "-".to_owned(),
+            Self::Equal => "=".to_owned(),
+            Self::NotEqual => "!=".to_owned(),
+            Self::Less => "<".to_owned(),
+            Self::LessEqual => "<=".to_owned(),
+            Self::Greater => ">".to_owned(),
+            Self::GreaterEqual => ">=".to_owned(),
+            Self::Star => "*".to_owned(),
+            Self::Literal(x) => format!("\"{x}\""),
+            Self::Number(x) => x.to_string(),
+            Self::Name(x) => x.clone(),
+            Self::Variable(x) => format!("${x}")
+        }
+    }
+}
+
+fn is_name_start(c: char) -> bool
+{
+    c.is_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_name_char(c: char) -> bool
+{
+    c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || !c.is_ascii()
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, XPathError>
+{
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next()
+    {
+        let mut next_is = |expected: char|
+        {
+            chars.next_if(|(_, c)| *c == expected).is_some()
+        };
+
+        let token = match c
+        {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Equal,
+            '*' => Token::Star,
+            '/' => if next_is('/') { Token::DoubleSlash } else { Token::Slash },
+            '<' => if next_is('=') { Token::LessEqual } else { Token::Less },
+            '>' => if next_is('=') { Token::GreaterEqual } else { Token::Greater },
+            ':' if next_is(':') => Token::ColonColon,
+            '!' if next_is('=') => Token::NotEqual,
+            '.' if next_is('.') => Token::DotDot,
+            '.' if !chars.peek().is_some_and(|(_, c)| c.is_ascii_digit()) => Token::Dot,
+            '"' | '\'' =>
+            {
+                let mut literal = String::new();
+                loop
+                {
+                    match chars.next()
+                    {
+                        Some((_, x)) if x == c => break,
+                        Some((_, x)) => literal.push(x),
+                        None => return Err(XPathError::new(XPathErrorKind::UnexpectedEnd, c, Some(text.len())))
+                    }
+                }
+
+                Token::Literal(literal)
+            },
+            c if c.is_ascii_digit() || c == '.' =>
+            {
+                let mut number = String::from(c);
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                {
+                    number.push(c);
+                }
+
+                match number.parse()
+                {
+                    Ok(x) => Token::Number(x),
+                    Err(_) => return Err(XPathError::new(XPathErrorKind::UnexpectedToken, number, Some(offset)))
+                }
+            },
+            '$' =>
+            {
+                let mut name = String::new();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_name_char(*c) || *c == ':')
+                {
+                    name.push(c);
+                }
+
+                Token::Variable(name)
+            },
+            c if is_name_start(c) =>
+            {
+                let mut name = String::from(c);
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_name_char(*c))
+                {
+                    name.push(c);
+                }
+
+                // a single colon is a prefix, two of them r an axis
+                let mut lookahead = chars.clone();
+                if lookahead.next().is_some_and(|(_, c)| c == ':')
+                {
+                    match lookahead.next()
+                    {
+                        Some((_, '*')) =>
+                        {
+                            chars.next();
+                            chars.next();
+
+                            name.push_str(":*");
+                        },
+                        Some((_, c)) if is_name_start(c) =>
+                        {
+                            chars.next();
+
+                            name.push(':');
+                            while let Some((_, c)) = chars.next_if(|(_, c)| is_name_char(*c))
+                            {
+                                name.push(c);
+                            }
+                        },
+                        _ => ()
+                    }
+                }
+
+                Token::Name(name)
+            },
+            c => return Err(XPathError::new(XPathErrorKind::UnexpectedToken, c, Some(offset)))
+        };
+
+        tokens.push((token, offset));
+    }
+
+    Ok(tokens)
+}
+
+pub fn parse_expression(text: &str) -> Result<Expr, XPathError>
+{
+    let mut parser = XPathParser{tokens: tokenize(text)?, index: 0, length: text.len()};
+
+    let expr = parser.parse_or()?;
+
+    match parser.peek()
+    {
+        None => Ok(expr),
+        Some(_) => Err(parser.unexpected())
+    }
+}
+
+struct XPathParser
+{
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    length: usize
+}
+
+impl XPathParser
+{
+    fn peek(&self) -> Option<&Token>
+    {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<&Token>
+    {
+        self.tokens.get(self.index + ahead).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token>
+    {
+        let token = self.tokens.get(self.index).map(|(token, _)| token.clone());
+        self.index += 1;
+
+        token
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool
+    {
+        let is_next = self.peek() == Some(expected);
+        if is_next
+        {
+            self.index += 1;
+        }
+
+        is_next
+    }
+
+    fn next_if_name(&mut self, expected: &str) -> bool
+    {
+        let is_next = matches!(self.peek(), Some(Token::Name(name)) if name == expected);
+        if is_next
+        {
+            self.index += 1;
+        }
+
+        is_next
+    }
+
+    fn offset(&self) -> usize
+    {
+        self.tokens.get(self.index).map(|(_, offset)| *offset).unwrap_or(self.length)
+    }
+
+    fn unexpected(&self) -> XPathError
+    {
+        match self.peek()
+        {
+            Some(token) => XPathError::new(XPathErrorKind::UnexpectedToken, token.describe(), Some(self.offset())),
+            None => XPathError::new(XPathErrorKind::UnexpectedEnd, "", Some(self.length))
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), XPathError>
+    {
+        if self.next_if(&expected)
+        {
+            Ok(())
+        } else
+        {
+            Err(self.unexpected())
+        }
+    }
+
+    fn binary(
+        &mut self,
+        operand: impl Fn(&mut Self) -> Result<Expr, XPathError>,
+        operator: impl Fn(&mut Self) -> Option<Operator>
+    ) -> Result<Expr, XPathError>
+    {
+        let mut expr = operand(self)?;
+
+        while let Some(operator) = operator(self)
+        {
+            expr = Expr::Binary(Box::new(expr), operator, Box::new(operand(self)?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_and, |this| this.next_if_name("or").then_some(Operator::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_equality, |this| this.next_if_name("and").then_some(Operator::And))
+    }
+
+    fn parse_equality(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_relational, |this|
+        {
+            if this.next_if(&Token::Equal)
+            {
+                Some(Operator::Equal)
+            } else if this.next_if(&Token::NotEqual)
+            {
+                Some(Operator::NotEqual)
+            } else
+            {
+                None
+            }
+        })
+    }
+
+    fn parse_relational(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_additive, |this|
+        {
+            let operator = match this.peek()?
+            {
+                Token::Less => Operator::Less,
+                Token::LessEqual => Operator::LessEqual,
+                Token::Greater => Operator::Greater,
+                Token::GreaterEqual => Operator::GreaterEqual,
+                _ => return None
+            };
+
+            this.next();
+
+            Some(operator)
+        })
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_multiplicative, |this|
+        {
+            if this.next_if(&Token::Plus)
+            {
+                Some(Operator::Add)
+            } else if this.next_if(&Token::Minus)
+            {
+                Some(Operator::Subtract)
+            } else
+            {
+                None
+            }
+        })
+    }
+
+    // a star or a name here is always an operator, in a step it would be a name test
+    fn parse_multiplicative(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_unary, |this|
+        {
+            if this.next_if(&Token::Star)
+            {
+                Some(Operator::Multiply)
+            } else if this.next_if_name("div")
+            {
+                Some(Operator::Divide)
+            } else if this.next_if_name("mod")
+            {
+                Some(Operator::Modulo)
+            } else
+            {
+                None
+            }
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, XPathError>
+    {
+        if self.next_if(&Token::Minus)
+        {
+            Ok(Expr::Negate(Box::new(self.parse_unary()?)))
+        } else
+        {
+            self.parse_union()
+        }
+    }
+
+    fn parse_union(&mut self) -> Result<Expr, XPathError>
+    {
+        self.binary(Self::parse_path, |this| this.next_if(&Token::Pipe).then_some(Operator::Union))
+    }
+
+    fn is_step_start(&self) -> bool
+    {
+        matches!(
+            self.peek(),
+            Some(Token::Dot | Token::DotDot | Token::At | Token::Star | Token::Name(_))
+        )
+    }
+
+    fn is_primary_start(&self) -> bool
+    {
+        match self.peek()
+        {
+            Some(Token::Literal(_) | Token::Number(_) | Token::Variable(_) | Token::LeftParen) => true,
+            Some(Token::Name(name)) =>
+            {
+                self.peek_at(1) == Some(&Token::LeftParen) && NodeTest::from_type(name).is_none()
+            },
+            _ => false
+        }
+    }
+
+    fn parse_path(&mut self) -> Result<Expr, XPathError>
+    {
+        if self.next_if(&Token::Slash)
+        {
+            let steps = if self.is_step_start()
+            {
+                self.parse_relative(Vec::new())?
+            } else
+            {
+                Vec::new()
+            };
+
+            return Ok(Expr::Path(PathStart::Root, steps));
+        }
+
+        if self.next_if(&Token::DoubleSlash)
+        {
+            let steps = self.parse_relative(vec![Step::descendant_or_self()])?;
+
+            return Ok(Expr::Path(PathStart::Root, steps));
+        }
+
+        if !self.is_primary_start()
+        {
+            return Ok(Expr::Path(PathStart::Context, self.parse_relative(Vec::new())?));
+        }
+
+        let primary = self.parse_primary()?;
+        let predicates = self.parse_predicates()?;
+
+        let filter = if predicates.is_empty()
+        {
+            primary
+        } else
+        {
+            Expr::Filter(Box::new(primary), predicates)
+        };
+
+        let steps = if self.next_if(&Token::Slash)
+        {
+            self.parse_relative(Vec::new())?
+        } else if self.next_if(&Token::DoubleSlash)
+        {
+            self.parse_relative(vec![Step::descendant_or_self()])?
+        } else
+        {
+            return Ok(filter);
+        };
+
+        Ok(Expr::Path(PathStart::Filter(Box::new(filter)), steps))
+    }
+
+    fn parse_relative(&mut self, mut steps: Vec<Step>) -> Result<Vec<Step>, XPathError>
+    {
+        steps.push(self.parse_step()?);
+
+        loop
+        {
+            if self.next_if(&Token::Slash)
+            {
+                steps.push(self.parse_step()?);
+            } else if self.next_if(&Token::DoubleSlash)
+            {
+                steps.push(Step::descendant_or_self());
+                steps.push(self.parse_step()?);
+            } else
+            {
+                return Ok(steps);
+            }
+        }
+    }
+
+    fn parse_step(&mut self) -> Result<Step, XPathError>
+    {
+        if self.next_if(&Token::Dot)
+        {
+            return Ok(Step{axis: Axis::SelfNode, test: NodeTest::Node, predicates: Vec::new()});
+        }
+
+        if self.next_if(&Token::DotDot)
+        {
+            return Ok(Step{axis: Axis::Parent, test: NodeTest::Node, predicates: Vec::new()});
+        }
+
+        let axis = if self.next_if(&Token::At)
+        {
+            Axis::Attribute
+        } else if let (Some(Token::Name(name)), Some(Token::ColonColon)) = (self.peek(), self.peek_at(1))
+        {
+            let offset = self.offset();
+            let axis = Axis::from_name(name)
+                .ok_or_else(|| XPathError::new(XPathErrorKind::UnexpectedToken, name, Some(offset)))?;
+
+            self.index += 2;
+
+            axis
+        } else
+        {
+            Axis::Child
+        };
+
+        let test = self.parse_node_test()?;
+        let predicates = self.parse_predicates()?;
+
+        Ok(Step{axis, test, predicates})
+    }
+
+    fn parse_node_test(&mut self) -> Result<NodeTest, XPathError>
+    {
+        let name = match self.peek()
+        {
+            Some(Token::Star) =>
+            {
+                self.next();
+
+                return Ok(NodeTest::Any);
+            },
+            Some(Token::Name(name)) => name.clone(),
+            _ => return Err(self.unexpected())
+        };
+
+        self.next();
+
+        if self.peek() == Some(&Token::LeftParen)
+        {
+            if let Some(test) = NodeTest::from_type(&name)
+            {
+                self.next();
+
+                let test = match (test, self.peek())
+                {
+                    (NodeTest::ProcessingInstruction(_), Some(Token::Literal(target))) =>
+                    {
+                        let target = target.clone();
+                        self.next();
+
+                        NodeTest::ProcessingInstruction(Some(target))
+                    },
+                    (test, _) => test
+                };
+
+                self.expect(Token::RightParen)?;
+
+                return Ok(test);
+            }
+        }
+
+        if let Some(prefix) = name.strip_suffix(":*")
+        {
+            return Ok(NodeTest::Prefix(prefix.to_owned()));
+        }
+
+        Ok(NodeTest::Name(name))
+    }
+
+    fn parse_predicates(&mut self) -> Result<Vec<Expr>, XPathError>
+    {
+        let mut predicates = Vec::new();
+
+        while self.next_if(&Token::LeftBracket)
+        {
+            predicates.push(self.parse_or()?);
+
+            self.expect(Token::RightBracket)?;
+        }
+
+        Ok(predicates)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, XPathError>
+    {
+        let offset = self.offset();
+
+        match self.next()
+        {
+            Some(Token::Literal(x)) => Ok(Expr::Literal(x)),
+            Some(Token::Number(x)) => Ok(Expr::Number(x)),
+            Some(Token::Variable(name)) =>
+            {
+                Err(XPathError::new(XPathErrorKind::Unsupported, format!("${name}"), Some(offset)))
+            },
+            Some(Token::LeftParen) =>
+            {
+                let expr = self.parse_or()?;
+                self.expect(Token::RightParen)?;
+
+                Ok(expr)
+            },
+            Some(Token::Name(name)) =>
+            {
+                self.expect(Token::LeftParen)?;
+
+                let mut arguments = Vec::new();
+                if !self.next_if(&Token::RightParen)
+                {
+                    loop
+                    {
+                        arguments.push(self.parse_or()?);
+
+                        if !self.next_if(&Token::Comma)
+                        {
+                            break;
+                        }
+                    }
+
+                    self.expect(Token::RightParen)?;
+                }
+
+                let (min, max) = functions::arity(&name)
+                    .ok_or_else(|| XPathError::new(XPathErrorKind::UnknownFunction, &name, Some(offset)))?;
+
+                if arguments.len() < min || arguments.len() > max
+                {
+                    return Err(XPathError::new(XPathErrorKind::WrongArguments, name, Some(offset)));
+                }
+
+                Ok(Expr::Function(name, arguments))
+            },
+            _ =>
+            {
+                self.index -= 1;
+
+                Err(self.unexpected())
+            }
+        }
+    }
+}